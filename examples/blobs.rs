@@ -0,0 +1,51 @@
+use std::{fs::File, io::BufWriter};
+
+use rand::prelude::*;
+
+use circle_packing::*;
+
+pub fn main() {
+    let mut rng = thread_rng();
+
+    let settings = Settings {
+        min_radius: 3.0,
+        padding: 3.0,
+        inside: true,
+        palette: vec![
+            "#ce565e".to_string(),
+            "#8e1752".to_string(),
+            "#f8a100".to_string(),
+            "#3ac1a6".to_string(),
+        ],
+        target_area: 0.8,
+        max_stall_iterations: 1000,
+        stop: None,
+        placement: Placement::First,
+        sampler: CandidateSampler::Uniform,
+    };
+
+    let moon = Difference::new(
+        Circle::new(0.0, 0.0, 250.0),
+        Circle::new(120.0, -60.0, 200.0),
+    );
+    let mut moon = PackShape::new(moon);
+    moon.color = 1 % settings.palette.len();
+    circle_packing::pack(&mut moon, &settings, &mut rng);
+
+    let blobs = SmoothUnion::new(
+        Circle::new(0.0, 0.0, 150.0),
+        Circle::new(250.0, 50.0, 100.0),
+        120.0,
+    );
+    let mut blobs = PackShape::new(blobs);
+    blobs.color = 1 % settings.palette.len();
+    circle_packing::pack(&mut blobs, &settings, &mut rng);
+
+    let f = File::create("packed_moon.svg").unwrap();
+    let mut bf = BufWriter::new(f);
+    dump_svg(&mut bf, &[moon], &settings).unwrap();
+
+    let f = File::create("packed_blobs.svg").unwrap();
+    let mut bf = BufWriter::new(f);
+    dump_svg(&mut bf, &[blobs], &settings).unwrap();
+}
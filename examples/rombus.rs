@@ -11,9 +11,17 @@ pub fn main() {
         min_radius: 5.0,
         padding: 5.0,
         inside: true,
-        palette: &["#ec6c26", "#613a53", "#e8ac52", "#639aa0"],
+        palette: vec![
+            "#ec6c26".to_string(),
+            "#613a53".to_string(),
+            "#e8ac52".to_string(),
+            "#639aa0".to_string(),
+        ],
         target_area: 0.8,
         max_stall_iterations: 1000,
+        stop: None,
+        placement: Placement::First,
+        sampler: CandidateSampler::Uniform,
     };
 
     let mut container = Polyline::new(vec![
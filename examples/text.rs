@@ -0,0 +1,56 @@
+use std::{env, fs::File, io::BufWriter};
+
+use rand::prelude::*;
+
+use circle_packing::{text::TextStyle, *};
+
+pub fn main() {
+    let mut args = env::args().skip(1);
+    let (font, text) = match (args.next(), args.next()) {
+        (Some(font), Some(text)) => (font, text),
+        _ => {
+            eprintln!("usage: text <font.ttf> <text>");
+            return;
+        }
+    };
+
+    let mut rng = thread_rng();
+
+    let settings = Settings {
+        min_radius: 2.0,
+        padding: 2.0,
+        inside: true,
+        palette: vec![
+            "#f46e26".to_string(),
+            "#68485f".to_string(),
+            "#3d273a".to_string(),
+            "#535d55".to_string(),
+        ],
+        target_area: 0.8,
+        max_stall_iterations: 1000,
+        stop: None,
+        placement: Placement::First,
+        sampler: CandidateSampler::Uniform,
+    };
+
+    let style = TextStyle {
+        size: 400.0,
+        letter_spacing: 20.0,
+        tolerance: 0.5,
+    };
+
+    let mut glyphs = text::load(font, &text, &style)
+        .unwrap()
+        .into_iter()
+        .map(PackShape::new)
+        .collect::<Vec<_>>();
+
+    for glyph in &mut glyphs {
+        glyph.color = 1 % settings.palette.len();
+        circle_packing::pack(glyph, &settings, &mut rng);
+    }
+
+    let f = File::create("packed_text.svg").unwrap();
+    let mut bf = BufWriter::new(f);
+    dump_svg(&mut bf, &glyphs, &settings).unwrap();
+}
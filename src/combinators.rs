@@ -0,0 +1,275 @@
+use std::{
+    fmt::Debug,
+    io::{self, Write},
+};
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{contour, Bbox, Shape};
+
+/// Number of samples per side used to estimate the area of a combination.
+const AREA_SAMPLES: usize = 256;
+
+/// Number of samples per side used to extract the contour when writing SVGs.
+const CONTOUR_SAMPLES: f32 = 256.0;
+
+/// How the sdfs of the two shapes of a `Combination` are merged together.
+pub trait Operator: Clone + Debug {
+    fn sdf(&self, a: f32, b: f32) -> f32;
+    fn bbox(&self, a: Bbox, b: Bbox) -> Bbox;
+}
+
+/// Shape obtained by combining the sdfs of two other shapes.
+///
+/// The area is estimated by sampling the sdf and the SVG output is a path
+/// following its contour, both are computed once when the combination is
+/// created. Beware that the combined sdf is usually just a bound of the real
+/// distance.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Combination<A, B, O> {
+    a: A,
+    b: B,
+    op: O,
+
+    bbox: Bbox,
+    area: f32,
+}
+
+pub type Union<A, B> = Combination<A, B, UnionOp>;
+pub type Intersection<A, B> = Combination<A, B, IntersectionOp>;
+pub type Difference<A, B> = Combination<A, B, DifferenceOp>;
+pub type SmoothUnion<A, B> = Combination<A, B, SmoothUnionOp>;
+pub type SmoothIntersection<A, B> = Combination<A, B, SmoothIntersectionOp>;
+pub type SmoothDifference<A, B> = Combination<A, B, SmoothDifferenceOp>;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnionOp;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IntersectionOp;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DifferenceOp;
+
+/// Union that blends the shapes together where they are closer than `k`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SmoothUnionOp {
+    pub k: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SmoothIntersectionOp {
+    pub k: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SmoothDifferenceOp {
+    pub k: f32,
+}
+
+impl<A: Shape, B: Shape, O: Operator> Combination<A, B, O> {
+    pub fn with_op(a: A, b: B, op: O) -> Self {
+        let bbox = op.bbox(a.bbox(), b.bbox());
+
+        let mut combination = Self {
+            a,
+            b,
+            op,
+            bbox,
+            area: 0.0,
+        };
+        combination.area = combination.estimate_area();
+        combination
+    }
+
+    pub fn a(&self) -> &A {
+        &self.a
+    }
+
+    pub fn b(&self) -> &B {
+        &self.b
+    }
+
+    /// Whether no sample of the sdf is inside, e.g. for the intersection of
+    /// disjoint shapes. Empty combinations aren't written to SVGs and their
+    /// random points are all at the center.
+    pub fn is_empty(&self) -> bool {
+        self.area <= 0.0
+    }
+
+    fn estimate_area(&self) -> f32 {
+        let dx = self.bbox.width() / AREA_SAMPLES as f32;
+        let dy = self.bbox.height() / AREA_SAMPLES as f32;
+
+        let mut inside = 0;
+        for j in 0..AREA_SAMPLES {
+            let y = self.bbox.y0() + (j as f32 + 0.5) * dy;
+            for i in 0..AREA_SAMPLES {
+                let x = self.bbox.x0() + (i as f32 + 0.5) * dx;
+                if self.sdf(x, y) <= 0.0 {
+                    inside += 1;
+                }
+            }
+        }
+
+        inside as f32 * dx * dy
+    }
+}
+
+impl<A: Shape, B: Shape> Union<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self::with_op(a, b, UnionOp)
+    }
+}
+
+impl<A: Shape, B: Shape> Intersection<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self::with_op(a, b, IntersectionOp)
+    }
+}
+
+impl<A: Shape, B: Shape> Difference<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self::with_op(a, b, DifferenceOp)
+    }
+}
+
+impl<A: Shape, B: Shape> SmoothUnion<A, B> {
+    pub fn new(a: A, b: B, k: f32) -> Self {
+        Self::with_op(a, b, SmoothUnionOp { k })
+    }
+}
+
+impl<A: Shape, B: Shape> SmoothIntersection<A, B> {
+    pub fn new(a: A, b: B, k: f32) -> Self {
+        Self::with_op(a, b, SmoothIntersectionOp { k })
+    }
+}
+
+impl<A: Shape, B: Shape> SmoothDifference<A, B> {
+    pub fn new(a: A, b: B, k: f32) -> Self {
+        Self::with_op(a, b, SmoothDifferenceOp { k })
+    }
+}
+
+impl<A: Shape, B: Shape, O: Operator> Shape for Combination<A, B, O> {
+    fn bbox(&self) -> Bbox {
+        self.bbox.clone()
+    }
+
+    fn center(&self) -> (f32, f32) {
+        self.bbox.center()
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        self.op.sdf(self.a.sdf(x, y), self.b.sdf(x, y))
+    }
+
+    fn area(&self) -> f32 {
+        self.area
+    }
+
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        if self.is_empty() {
+            return self.center();
+        }
+
+        loop {
+            let (x, y) = self.bbox.random_point(rng);
+            if self.sdf(x, y) <= 0.0 {
+                break (x, y);
+            }
+        }
+    }
+
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let step = self.bbox.width().max(self.bbox.height()) / CONTOUR_SAMPLES;
+
+        writeln!(
+            w,
+            r#"<path d="{}" fill="{}" stroke="{}" fill-rule="evenodd"/>"#,
+            contour::svg_path(&contour::contours(self, step)),
+            fill,
+            stroke
+        )
+    }
+}
+
+impl Operator for UnionOp {
+    fn sdf(&self, a: f32, b: f32) -> f32 {
+        a.min(b)
+    }
+
+    fn bbox(&self, a: Bbox, b: Bbox) -> Bbox {
+        a.union(&b)
+    }
+}
+
+impl Operator for IntersectionOp {
+    fn sdf(&self, a: f32, b: f32) -> f32 {
+        a.max(b)
+    }
+
+    fn bbox(&self, a: Bbox, b: Bbox) -> Bbox {
+        a.intersection(&b)
+    }
+}
+
+impl Operator for DifferenceOp {
+    fn sdf(&self, a: f32, b: f32) -> f32 {
+        a.max(-b)
+    }
+
+    fn bbox(&self, a: Bbox, _b: Bbox) -> Bbox {
+        a
+    }
+}
+
+impl Operator for SmoothUnionOp {
+    fn sdf(&self, a: f32, b: f32) -> f32 {
+        smooth_min(a, b, self.k)
+    }
+
+    fn bbox(&self, a: Bbox, b: Bbox) -> Bbox {
+        // the blend pushes the surface out by at most k / 4
+        let mut bbox = a.union(&b);
+        bbox.expand(bbox.x0() - self.k / 4.0, bbox.y0() - self.k / 4.0);
+        bbox.expand(bbox.x1() + self.k / 4.0, bbox.y1() + self.k / 4.0);
+        bbox
+    }
+}
+
+impl Operator for SmoothIntersectionOp {
+    fn sdf(&self, a: f32, b: f32) -> f32 {
+        -smooth_min(-a, -b, self.k)
+    }
+
+    fn bbox(&self, a: Bbox, b: Bbox) -> Bbox {
+        a.intersection(&b)
+    }
+}
+
+impl Operator for SmoothDifferenceOp {
+    fn sdf(&self, a: f32, b: f32) -> f32 {
+        -smooth_min(-a, b, self.k)
+    }
+
+    fn bbox(&self, a: Bbox, _b: Bbox) -> Bbox {
+        a
+    }
+}
+
+/// Polynomial smooth minimum, see https://iquilezles.org/articles/smin/.
+fn smooth_min(a: f32, b: f32, k: f32) -> f32 {
+    if k <= 0.0 {
+        return a.min(b);
+    }
+
+    let h = (k - (a - b).abs()).max(0.0) / k;
+    a.min(b) - h * h * k / 4.0
+}
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+
+use crate::Shape;
+
+/// Edge between two adjacent samples of the grid, identified by the
+/// coordinates of its first sample and whether it's vertical or not.
+type Edge = (usize, usize, bool);
+
+/// Extract the closed contours of the zero level set of the sdf of the given
+/// shape using marching squares with samples `step` apart, there are none if
+/// `step` is not positive.
+///
+/// The orientation of the contours is not meaningful, use the even-odd rule to
+/// fill them.
+pub fn contours(shape: &impl Shape, step: f32) -> Vec<Vec<(f32, f32)>> {
+    if step.is_nan() || step <= 0.0 {
+        return vec![];
+    }
+
+    let bbox = shape.bbox();
+
+    // leave a ring of samples around the shape so that all contours are closed
+    let x0 = bbox.x0() - step;
+    let y0 = bbox.y0() - step;
+    let nx = (bbox.width() / step).ceil() as usize + 3;
+    let ny = (bbox.height() / step).ceil() as usize + 3;
+
+    let point = |i: usize, j: usize| (x0 + i as f32 * step, y0 + j as f32 * step);
+
+    let mut values = Vec::with_capacity(nx * ny);
+    for j in 0..ny {
+        for i in 0..nx {
+            let (x, y) = point(i, j);
+            values.push(shape.sdf(x, y));
+        }
+    }
+    let value = |i: usize, j: usize| values[j * nx + i];
+
+    let crossing = |(i, j, vertical): Edge| {
+        let (i1, j1) = if vertical { (i, j + 1) } else { (i + 1, j) };
+        let (v0, v1) = (value(i, j), value(i1, j1));
+        let t = v0 / (v0 - v1);
+
+        let (px, py) = point(i, j);
+        if vertical {
+            (px, py + t * step)
+        } else {
+            (px + t * step, py)
+        }
+    };
+
+    let mut links: BTreeMap<Edge, Vec<Edge>> = BTreeMap::new();
+    let mut link = |e0: Edge, e1: Edge| {
+        links.entry(e0).or_default().push(e1);
+        links.entry(e1).or_default().push(e0);
+    };
+
+    for j in 0..ny - 1 {
+        for i in 0..nx - 1 {
+            let tl = value(i, j) < 0.0;
+            let tr = value(i + 1, j) < 0.0;
+            let br = value(i + 1, j + 1) < 0.0;
+            let bl = value(i, j + 1) < 0.0;
+
+            let top = (i, j, false);
+            let right = (i + 1, j, true);
+            let bottom = (i, j + 1, false);
+            let left = (i, j, true);
+
+            let mut edges = vec![];
+            if tl != tr {
+                edges.push(top);
+            }
+            if tr != br {
+                edges.push(right);
+            }
+            if br != bl {
+                edges.push(bottom);
+            }
+            if bl != tl {
+                edges.push(left);
+            }
+
+            match edges.len() {
+                2 => link(edges[0], edges[1]),
+                4 => {
+                    // saddle, use the value at the center of the cell to
+                    // decide which corners are connected
+                    let center =
+                        (value(i, j) + value(i + 1, j) + value(i + 1, j + 1) + value(i, j + 1))
+                            / 4.0;
+                    if (center < 0.0) == tl {
+                        link(top, right);
+                        link(bottom, left);
+                    } else {
+                        link(left, top);
+                        link(right, bottom);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut contours = vec![];
+    while let Some(&start) = links.keys().next() {
+        let mut contour = vec![crossing(start)];
+
+        let (mut prev, mut cur) = (start, links[&start][0]);
+        while cur != start {
+            contour.push(crossing(cur));
+
+            let next = match links.remove(&cur) {
+                Some(ns) if ns.len() == 2 => {
+                    if ns[0] == prev {
+                        ns[1]
+                    } else {
+                        ns[0]
+                    }
+                }
+                _ => break,
+            };
+
+            prev = cur;
+            cur = next;
+        }
+        links.remove(&start);
+
+        contours.push(contour);
+    }
+
+    contours
+}
+
+/// Format the given closed contours as the `d` attribute of an SVG path.
+pub fn svg_path(contours: &[Vec<(f32, f32)>]) -> String {
+    let mut d = String::new();
+
+    for contour in contours {
+        for (i, (x, y)) in contour.iter().enumerate() {
+            d += &format!("{} {},{} ", if i == 0 { "M" } else { "L" }, x, y);
+        }
+        d += "Z ";
+    }
+
+    d.trim_end().to_string()
+}
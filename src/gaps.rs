@@ -0,0 +1,233 @@
+//! Search of the largest empty circle among the shapes packed so far, used by
+//! `Placement::LargestEmpty`.
+//!
+//! The clearance of a point is the radius of the biggest circle centered there
+//! that keeps the padding from the container and the packed shapes. Since sdfs
+//! are distances, the clearance of any point of a square cell is at most the
+//! one of its center plus half its diagonal. Cells are kept in a max-heap by
+//! this bound and the top one is split until it's smaller than the tolerance,
+//! like polylabel does. Packing a shape only lowers clearances, therefore the
+//! bounds of the cells stay valid and they're only updated once popped.
+//!
+//! When shapes can be packed inside other shapes each packed shape is searched
+//! as well, so that all the shapes are placed in decreasing radius order.
+
+use std::{cmp::Ordering, collections::BinaryHeap, f32::consts::SQRT_2};
+
+use crate::{Bbox, PackShape, Packable, Settings, Shape};
+
+/// Size of the smallest cells relative to the minimum radius.
+const TOLERANCE: f32 = 0.1;
+
+#[derive(Debug, Clone)]
+pub struct Gaps {
+    cells: BinaryHeap<Cell>,
+
+    /// Path of child indices from the root to each of the shapes searched.
+    nodes: Vec<Vec<usize>>,
+}
+
+/// Center of the largest empty circle found in a shape.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gap {
+    pub node: usize,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+}
+
+#[derive(Debug, Clone)]
+struct Cell {
+    /// Upper bound of the clearance of the points in the cell.
+    bound: f32,
+
+    x: f32,
+    y: f32,
+    half_side: f32,
+    node: usize,
+}
+
+impl Gaps {
+    /// Start searching the free space of the given root and, if shapes can be
+    /// packed inside other shapes, of the shapes already packed in it.
+    pub fn new<S: Shape, C: Packable>(root: &PackShape<S, C>, cfg: &Settings) -> Self {
+        let mut gaps = Gaps {
+            cells: BinaryHeap::new(),
+            nodes: vec![],
+        };
+
+        let mut stack = vec![vec![]];
+        while let Some(path) = stack.pop() {
+            if cfg.inside {
+                let n = children_len(root, &path);
+                stack.extend((0..n).map(|i| [&path[..], &[i]].concat()));
+            }
+            gaps.push_node(root, path, cfg);
+        }
+
+        gaps
+    }
+
+    /// Find the largest empty circle in any of the shapes, within the
+    /// tolerance, or `None` if it's smaller than the minimum radius.
+    ///
+    /// The cell the circle was found in is forgotten, the circle must be
+    /// packed and passed to `packed` before searching again.
+    pub fn next<S: Shape, C: Packable>(
+        &mut self,
+        root: &PackShape<S, C>,
+        cfg: &Settings,
+    ) -> Option<Gap> {
+        let tolerance = TOLERANCE * cfg.min_radius;
+
+        while let Some(cell) = self.cells.pop() {
+            if cell.bound < cfg.min_radius {
+                return None;
+            }
+
+            let path = &self.nodes[cell.node];
+            let d = clearance(root, path, cell.x, cell.y, cfg);
+            let bound = d + cell.half_side * SQRT_2;
+            if bound < cfg.min_radius {
+                continue;
+            }
+
+            // shapes were packed since the bound was computed
+            if bound < cell.bound {
+                self.cells.push(Cell { bound, ..cell });
+                continue;
+            }
+
+            if cell.half_side * SQRT_2 <= tolerance {
+                if d >= cfg.min_radius {
+                    return Some(Gap {
+                        node: cell.node,
+                        x: cell.x,
+                        y: cell.y,
+                        radius: d,
+                    });
+                }
+                continue;
+            }
+
+            let h = cell.half_side / 2.0;
+            for (dx, dy) in [(-h, -h), (h, -h), (-h, h), (h, h)] {
+                self.push_cell(root, cell.x + dx, cell.y + dy, h, cell.node, cfg);
+            }
+        }
+
+        None
+    }
+
+    /// Record that a shape was packed in the given gap, so that its inside is
+    /// searched too if possible.
+    pub fn packed<S: Shape, C: Packable>(
+        &mut self,
+        root: &PackShape<S, C>,
+        gap: &Gap,
+        cfg: &Settings,
+    ) {
+        if !cfg.inside {
+            return;
+        }
+
+        let parent = &self.nodes[gap.node];
+        let path = [&parent[..], &[children_len(root, parent) - 1]].concat();
+        self.push_node(root, path, cfg);
+    }
+
+    /// Cover the bbox of the shape at the given path with square cells.
+    fn push_node<S: Shape, C: Packable>(
+        &mut self,
+        root: &PackShape<S, C>,
+        path: Vec<usize>,
+        cfg: &Settings,
+    ) {
+        let bbox = bbox(root, &path);
+        let side = bbox.width().min(bbox.height());
+        if side <= 0.0 {
+            return;
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(path);
+
+        let cols = (bbox.width() / side).ceil() as usize;
+        let rows = (bbox.height() / side).ceil() as usize;
+        for r in 0..rows {
+            for c in 0..cols {
+                let x = bbox.x0() + (c as f32 + 0.5) * side;
+                let y = bbox.y0() + (r as f32 + 0.5) * side;
+                self.push_cell(root, x, y, side / 2.0, node, cfg);
+            }
+        }
+    }
+
+    fn push_cell<S: Shape, C: Packable>(
+        &mut self,
+        root: &PackShape<S, C>,
+        x: f32,
+        y: f32,
+        half_side: f32,
+        node: usize,
+        cfg: &Settings,
+    ) {
+        let bound = clearance(root, &self.nodes[node], x, y, cfg) + half_side * SQRT_2;
+        if bound >= cfg.min_radius {
+            self.cells.push(Cell {
+                bound,
+                x,
+                y,
+                half_side,
+                node,
+            });
+        }
+    }
+}
+
+fn clearance<S: Shape, C: Packable>(
+    shape: &PackShape<S, C>,
+    path: &[usize],
+    x: f32,
+    y: f32,
+    cfg: &Settings,
+) -> f32 {
+    match path.split_first() {
+        None => shape.clearance(x, y, cfg),
+        Some((&i, path)) => clearance(&shape.children[i], path, x, y, cfg),
+    }
+}
+
+fn bbox<S: Shape, C: Packable>(shape: &PackShape<S, C>, path: &[usize]) -> Bbox {
+    match path.split_first() {
+        None => shape.bbox(),
+        Some((&i, path)) => bbox(&shape.children[i], path),
+    }
+}
+
+fn children_len<S: Shape, C: Packable>(shape: &PackShape<S, C>, path: &[usize]) -> usize {
+    match path.split_first() {
+        None => shape.children.len(),
+        Some((&i, path)) => children_len(&shape.children[i], path),
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bound.total_cmp(&other.bound)
+    }
+}
@@ -0,0 +1,228 @@
+use crate::Bbox;
+
+/// Average number of children per cell above which the grid gets refined.
+const MAX_LOAD: usize = 4;
+
+/// Uniform grid laid over the bounding box of a container that buckets its
+/// children by their bounding boxes.
+///
+/// The grid starts with a single cell and it's refined as children are
+/// inserted so that the number of cells stays proportional to the number of
+/// children, the cells are laid over the extent given at refinement time which
+/// is usually the bbox of the container. Children whose bbox falls outside of
+/// the grid are clamped to the border cells which keeps the distance bounds
+/// used while searching valid.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    bbox: Bbox,
+    boxes: Vec<Bbox>,
+
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl Default for Grid {
+    /// Create an empty grid. No memory is allocated until the first insertion.
+    fn default() -> Self {
+        Self {
+            bbox: Bbox::new(0.0, 0.0),
+            boxes: vec![],
+            cell_size: f32::INFINITY,
+            cols: 1,
+            rows: 1,
+            cells: vec![],
+        }
+    }
+}
+
+impl Grid {
+    /// Register a new child with the given bbox, its id is the number of
+    /// children inserted before it. Cells are never refined below
+    /// `min_cell_size`.
+    pub fn insert(&mut self, bbox: Bbox, extent: &Bbox, min_cell_size: f32) {
+        let id = self.boxes.len();
+        self.boxes.push(bbox);
+
+        if self.cells.is_empty() {
+            self.cells = vec![vec![]];
+        }
+
+        if self.boxes.len() > self.cells.len() * MAX_LOAD && self.cell_size > min_cell_size {
+            self.refine(extent, min_cell_size);
+        } else {
+            self.bucket(id);
+        }
+    }
+
+    /// Forget the child with the given id, it won't be visited anymore but the
+    /// ids of the other children stay the same.
+    pub fn remove(&mut self, id: usize) {
+        if self.cells.is_empty() {
+            return;
+        }
+
+        let bbox = &self.boxes[id];
+        let (c0, r0) = self.cell_of(bbox.x0(), bbox.y0());
+        let (c1, r1) = self.cell_of(bbox.x1(), bbox.y1());
+
+        for r in r0..=r1 {
+            for c in c0..=c1 {
+                self.cells[r * self.cols + c].retain(|&i| i != id);
+            }
+        }
+    }
+
+    /// Ids of the children whose bbox might contain the given point, in
+    /// insertion order.
+    pub fn candidates(&self, x: f32, y: f32) -> &[usize] {
+        if self.cells.is_empty() {
+            return &[];
+        }
+
+        let (c, r) = self.cell_of(x, y);
+        &self.cells[r * self.cols + c]
+    }
+
+    /// Visit the children that might be closer than `max_dist` to the given
+    /// point, going outwards one ring of cells at a time.
+    ///
+    /// `f` is called with the id of each child and must return the new maximum
+    /// distance of interest, this allows the search to stop as soon as the
+    /// closest children have been found. Children spanning multiple cells might
+    /// be visited more than once.
+    pub fn visit_nearby(&self, x: f32, y: f32, mut max_dist: f32, mut f: impl FnMut(usize) -> f32) {
+        if self.cells.is_empty() {
+            return;
+        }
+
+        let (cc, cr) = self.cell_of(x, y);
+        let (cc, cr) = (cc as isize, cr as isize);
+        let max_ring = self.cols.max(self.rows) as isize;
+
+        let mut visit = |c: isize, r: isize, max_dist: &mut f32| {
+            if c < 0 || r < 0 || c >= self.cols as isize || r >= self.rows as isize {
+                return;
+            }
+
+            for &id in &self.cells[r as usize * self.cols + c as usize] {
+                *max_dist = f(id);
+            }
+        };
+
+        visit(cc, cr, &mut max_dist);
+
+        for k in 1..max_ring {
+            // the point lies in the center cell, therefore anything in the k-th
+            // ring is at least k - 1 cells away.
+            if (k - 1) as f32 * self.cell_size > max_dist {
+                break;
+            }
+
+            for c in cc - k..=cc + k {
+                visit(c, cr - k, &mut max_dist);
+                visit(c, cr + k, &mut max_dist);
+            }
+            for r in cr - k + 1..cr + k {
+                visit(cc - k, r, &mut max_dist);
+                visit(cc + k, r, &mut max_dist);
+            }
+        }
+    }
+
+    fn refine(&mut self, extent: &Bbox, min_cell_size: f32) {
+        let (w, h) = (extent.width(), extent.height());
+
+        let cell_size = (w * h / self.boxes.len() as f32).sqrt().max(min_cell_size);
+        if cell_size > 0.0 && cell_size.is_finite() {
+            self.bbox = extent.clone();
+            self.cell_size = cell_size;
+            self.cols = ((w / cell_size).ceil() as usize).max(1);
+            self.rows = ((h / cell_size).ceil() as usize).max(1);
+        }
+
+        self.cells = vec![vec![]; self.cols * self.rows];
+        for id in 0..self.boxes.len() {
+            self.bucket(id);
+        }
+    }
+
+    fn bucket(&mut self, id: usize) {
+        let bbox = &self.boxes[id];
+        let (c0, r0) = self.cell_of(bbox.x0(), bbox.y0());
+        let (c1, r1) = self.cell_of(bbox.x1(), bbox.y1());
+
+        for r in r0..=r1 {
+            for c in c0..=c1 {
+                self.cells[r * self.cols + c].push(id);
+            }
+        }
+    }
+
+    fn cell_of(&self, x: f32, y: f32) -> (usize, usize) {
+        let c = ((x - self.bbox.x0()) / self.cell_size).floor().max(0.0) as usize;
+        let r = ((y - self.bbox.y0()) / self.cell_size).floor().max(0.0) as usize;
+        (c.min(self.cols - 1), r.min(self.rows - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::Shape;
+
+    /// Insert random boxes, some spanning many cells, remove some of them and
+    /// check that searches find the same boxes as scanning all of them.
+    #[test]
+    fn visit_nearby_matches_a_scan() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let mut extent = Bbox::new(0.0, 0.0);
+        extent.expand(200.0, 100.0);
+
+        let mut grid = Grid::default();
+        let mut boxes = vec![];
+        for i in 0..300 {
+            let size = if i % 25 == 0 { 60.0 } else { 3.0 };
+            let (x, y) = (rng.gen_range(-20.0..220.0), rng.gen_range(-20.0..120.0));
+            let mut bbox = Bbox::new(x, y);
+            bbox.expand(x + rng.gen_range(0.5..size), y + rng.gen_range(0.5..size));
+
+            grid.insert(bbox.clone(), &extent, 2.0);
+            boxes.push(bbox);
+        }
+        assert!(grid.cols * grid.rows > 1);
+
+        let removed = (0..boxes.len()).filter(|i| i % 3 == 0).collect::<Vec<_>>();
+        for &i in &removed {
+            grid.remove(i);
+        }
+
+        for _ in 0..2000 {
+            let (x, y) = (rng.gen_range(-40.0..240.0), rng.gen_range(-40.0..140.0));
+            let max_dist = rng.gen_range(0.0..50.0);
+
+            let mut visited = vec![false; boxes.len()];
+            grid.visit_nearby(x, y, max_dist, |i| {
+                visited[i] = true;
+                max_dist
+            });
+
+            for (i, bbox) in boxes.iter().enumerate() {
+                if removed.contains(&i) {
+                    assert!(!visited[i], "removed box {} was visited", i);
+                } else if bbox.sdf(x, y) < max_dist {
+                    assert!(visited[i], "box {} at {:?} was missed", i, (x, y));
+                }
+            }
+
+            for (i, bbox) in boxes.iter().enumerate() {
+                let inside = bbox.sdf(x, y) <= 0.0 && !removed.contains(&i);
+                assert!(!inside || grid.candidates(x, y).contains(&i));
+            }
+        }
+    }
+}
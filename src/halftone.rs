@@ -0,0 +1,43 @@
+//! Halftone packings where the size of the shapes follows an image.
+
+use image::{DynamicImage, GrayImage};
+
+use crate::{paint, Bbox};
+
+/// Maximum radius at each point, interpolated between `min_radius` and
+/// `max_radius` by the luminance of an image stretched to cover an area.
+///
+/// Bright areas get the biggest shapes unless `invert` is set, use it with
+/// `pack_with_field`.
+#[derive(Debug, Clone)]
+pub struct RadiusField {
+    image: GrayImage,
+    area: Bbox,
+
+    pub min_radius: f32,
+    pub max_radius: f32,
+    pub invert: bool,
+}
+
+impl RadiusField {
+    pub fn new(image: &DynamicImage, area: Bbox, min_radius: f32, max_radius: f32) -> Self {
+        Self {
+            image: image.to_luma8(),
+            area,
+            min_radius,
+            max_radius,
+            invert: false,
+        }
+    }
+
+    pub fn radius(&self, x: f32, y: f32) -> f32 {
+        let (i, j) = paint::to_pixel(self.image.dimensions(), &self.area, x, y);
+
+        let mut t = f32::from(self.image.get_pixel(i, j).0[0]) / 255.0;
+        if self.invert {
+            t = 1.0 - t;
+        }
+
+        self.min_radius + (self.max_radius - self.min_radius) * t
+    }
+}
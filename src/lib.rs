@@ -1,12 +1,52 @@
 use std::{
     fmt::Debug,
     io::{self, Write},
+    time::Instant,
 };
 
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 
+pub mod combinators;
+pub mod contour;
+mod gaps;
+mod grid;
+pub mod halftone;
+pub mod mask;
+pub mod metadata;
+pub mod nesting;
+pub mod paint;
+pub mod path;
+pub mod pdf;
+pub mod placement;
+pub mod plot;
+pub mod progress;
+pub mod raster;
+pub mod sampler;
+pub mod scene;
 pub mod shapes;
-pub use shapes::{Bbox, Circle, Polyline};
+pub mod stop;
+pub mod svg;
+pub mod text;
+pub mod travel;
+pub mod tree;
+
+pub use combinators::{
+    Difference, Intersection, SmoothDifference, SmoothIntersection, SmoothUnion, Union,
+};
+use gaps::Gaps;
+use grid::Grid;
+pub use mask::Mask;
+pub use metadata::Metadata;
+pub use placement::Placement;
+use progress::{Observer, Progress, PROGRESS_INTERVAL};
+pub use sampler::CandidateSampler;
+use sampler::Candidates;
+pub use shapes::{
+    Annulus, Bbox, Capsule, Circle, Ellipse, Pie, Polyline, RegularPolygon, RoundedRect, Star,
+    Superellipse,
+};
+pub use stop::StopCriterion;
 
 pub trait Shape: Clone + Debug {
     fn bbox(&self) -> Bbox;
@@ -16,60 +56,276 @@ pub trait Shape: Clone + Debug {
 
     fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32);
     fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()>;
+
+    /// Write the PDF path construction operators of the outline of the shape,
+    /// which is then filled with the even-odd rule. By default the contour of
+    /// the sdf is traced.
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        pdf::write_contour(w, self)
+    }
+
+    /// Strokes drawing the outline of the shape with a pen, curves are
+    /// approximated within `tolerance` unless they can be drawn exactly. By
+    /// default the contour of the sdf is traced.
+    fn strokes(&self, _tolerance: f32) -> Vec<plot::Stroke> {
+        plot::contour_strokes(self)
+    }
+}
+
+/// A shape that can be packed inside a container.
+///
+/// The size of a packable shape is described by its scale which is the radius
+/// of the biggest circle centered in `center()` that fits in the shape.
+pub trait Packable: Shape {
+    /// Return a copy of this shape centered at the given point with the given
+    /// scale.
+    fn placed(&self, x: f32, y: f32, scale: f32) -> Self;
+    fn scale(&self) -> f32;
+
+    /// Ratio between the radius of the smallest circle centered in `center()`
+    /// containing the shape and the scale. It's 1 for circles.
+    fn outer_ratio(&self) -> f32;
+
+    /// Points on the boundary of the shape that are at most `tolerance` apart.
+    fn outline(&self, tolerance: f32) -> Vec<(f32, f32)>;
 }
 
 #[derive(Debug, Clone)]
-pub struct PackShape<S: Shape> {
+pub struct PackShape<S: Shape, C: Packable = Circle> {
     container: S,
-    children: Vec<PackShape<Circle>>,
+    children: Vec<PackShape<C, C>>,
+    index: Grid,
 
     occupied_area: f32,
     pub color: usize,
+
+    /// Fill color that takes precedence over `color`, e.g. the one sampled
+    /// from an image by `paint::paint`.
+    pub fill: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub min_radius: f32,
     pub padding: f32,
     pub inside: bool,
 
-    pub palette: &'static [&'static str],
+    pub palette: Vec<String>,
 
     pub target_area: f32,
     pub max_stall_iterations: usize,
+
+    /// When to stop packing, instead of once `target_area` is covered or
+    /// after `max_stall_iterations` failed attempts in a row.
+    pub stop: Option<StopCriterion>,
+
+    /// How the point where the next shape is placed is chosen.
+    pub placement: Placement,
+
+    /// Where the points tried by `placement` come from.
+    pub sampler: CandidateSampler,
+}
+
+impl Default for Settings {
+    /// The defaults of the command line program, with a blue and white
+    /// palette.
+    fn default() -> Self {
+        Self {
+            min_radius: 5.0,
+            padding: 5.0,
+            inside: true,
+            palette: vec!["#172a89".to_string(), "#f7f7f3".to_string()],
+            target_area: 0.8,
+            max_stall_iterations: 1000,
+            stop: None,
+            placement: Placement::First,
+            sampler: CandidateSampler::Uniform,
+        }
+    }
+}
+
+impl Settings {
+    /// The criterion in `stop`, or the one described by `target_area` and
+    /// `max_stall_iterations` if missing.
+    pub fn stop_criterion(&self) -> StopCriterion {
+        self.stop.clone().unwrap_or_else(|| {
+            StopCriterion::Any(vec![
+                StopCriterion::Coverage(self.target_area),
+                StopCriterion::Stall(self.max_stall_iterations),
+            ])
+        })
+    }
 }
 
 pub fn pack(root: &mut PackShape<impl Shape>, settings: &Settings, rng: &mut impl Rng) {
-    let target_area = settings.target_area * root.area();
+    pack_with(root, &Circle::new(0.0, 0.0, 1.0), settings, rng)
+}
 
-    let mut stall_i = 0;
-    while root.occupied_area() < target_area {
-        let (x, y) = root.random_point(rng);
-        let radius = -root.sdf(x, y) - settings.padding;
+/// Pack copies of the given child shape inside root, see `Packable::placed`.
+pub fn pack_with<C: Packable>(
+    root: &mut PackShape<impl Shape, C>,
+    child: &C,
+    settings: &Settings,
+    rng: &mut impl Rng,
+) {
+    pack_with_field(root, child, settings, |_, _| f32::INFINITY, rng)
+}
+
+/// Like `pack_with`, but the scale of the shape placed at any point is also
+/// limited by `max_scale` at that point, see `halftone::RadiusField`.
+pub fn pack_with_field<C: Packable>(
+    root: &mut PackShape<impl Shape, C>,
+    child: &C,
+    settings: &Settings,
+    max_scale: impl Fn(f32, f32) -> f32,
+    rng: &mut impl Rng,
+) {
+    pack_with_observer(root, child, settings, max_scale, &mut (), rng);
+}
 
-        let stall = !root.pack(PackShape::circle(x, y, radius), &settings);
+/// Like `pack_with_field`, but the given observer is told about the progress
+/// and can stop the packing early, see `progress::Observer`. Return the
+/// statistics of the whole run.
+pub fn pack_with_observer<C: Packable>(
+    root: &mut PackShape<impl Shape, C>,
+    child: &C,
+    settings: &Settings,
+    max_scale: impl Fn(f32, f32) -> f32,
+    observer: &mut impl Observer<C>,
+    rng: &mut impl Rng,
+) -> Progress {
+    let stop = settings.stop_criterion();
+    let area = root.area();
+    let start = Instant::now();
 
-        if stall {
-            stall_i += 1;
-            if stall_i >= settings.max_stall_iterations {
+    let mut progress = Progress {
+        placed: 0,
+        attempts: 0,
+        stall: 0,
+        coverage: root.occupied_area() / area,
+        acceptance_rate: 1.0,
+        elapsed: start.elapsed(),
+    };
+    let mut recently_placed = 0;
+    let mut candidates = settings
+        .sampler
+        .candidates(&root.bbox(), settings.min_radius, rng);
+
+    let mut gaps = match settings.placement {
+        Placement::LargestEmpty => Some(Gaps::new(root, settings)),
+        _ => None,
+    };
+
+    while !stop.is_met(&progress) {
+        let mut gap = None;
+        let best = match &mut gaps {
+            None => {
+                let k = settings.placement.candidates(progress.coverage);
+                best_candidate(root, &mut candidates, k, &max_scale, settings, rng)
+            }
+            Some(gaps) => {
+                let g = match gaps.next(root, settings) {
+                    Some(g) => g,
+                    // no gap is big enough anymore
+                    None => break,
+                };
+                let best = (g.x, g.y, g.radius.min(max_scale(g.x, g.y)));
+                gap = Some(g);
+                Some(best)
+            }
+        };
+
+        progress.attempts += 1;
+        // the placed shape has no children yet so it's cheap to copy
+        let placed = best.and_then(|(x, y, radius)| {
+            root.place(PackShape::new(child.placed(x, y, radius)), settings)
+                .cloned()
+        });
+        progress.elapsed = start.elapsed();
+
+        if let (Some(gaps), Some(gap), Some(_)) = (&mut gaps, &gap, &placed) {
+            gaps.packed(root, gap, settings);
+        }
+
+        match placed {
+            None => progress.stall += 1,
+            Some(shape) => {
+                progress.placed += 1;
+                progress.stall = 0;
+                progress.coverage = root.occupied_area() / area;
+                recently_placed += 1;
+
+                if observer.placed(&shape, &progress).is_break() {
+                    break;
+                }
+            }
+        }
+
+        if progress.attempts.is_multiple_of(PROGRESS_INTERVAL) {
+            progress.acceptance_rate = recently_placed as f32 / PROGRESS_INTERVAL as f32;
+            recently_placed = 0;
+
+            if observer.progress(&progress).is_break() {
                 break;
             }
+        }
+    }
+
+    progress
+}
+
+/// The best of `k` points from the given candidates together with the scale
+/// of the shape to place there, see `Placement::BestOf`.
+fn best_candidate<C: Packable>(
+    root: &PackShape<impl Shape, C>,
+    candidates: &mut Candidates,
+    k: usize,
+    max_scale: impl Fn(f32, f32) -> f32,
+    settings: &Settings,
+    rng: &mut impl Rng,
+) -> Option<(f32, f32, f32)> {
+    let mut best = None;
+    for _ in 0..k {
+        let (x, y) = candidates.next_in(root, rng);
+        let radius = (-root.sdf(x, y) - settings.padding).min(max_scale(x, y));
+
+        // a single candidate is checked by `place` anyway
+        let fit = if k == 1 {
+            Some(radius)
         } else {
-            stall_i = 0;
+            root.fit(x, y, radius, settings)
+        };
+        if let Some(fit) = fit {
+            if best.is_none_or(|(_, _, _, f)| fit > f) {
+                best = Some((x, y, radius, fit));
+            }
         }
     }
+
+    best.map(|(x, y, radius, _)| (x, y, radius))
 }
 
-pub fn dump_svg<S: Shape>(
+/// Write the given roots as an SVG, with the settings and the version of the
+/// crate in its `<metadata>`. There must be at least one root, otherwise an
+/// `InvalidInput` error is returned, like by the other outputs.
+pub fn dump_svg<S: Shape, C: Packable>(
     out: &mut impl Write,
-    roots: &[PackShape<S>],
+    roots: &[PackShape<S, C>],
     cfg: &Settings,
 ) -> io::Result<()> {
-    let mut bbox = roots[0].bbox();
-    for s in &roots[1..] {
-        let b = s.bbox();
-        bbox.expand(b.x0(), b.y0());
-        bbox.expand(b.x0() + b.width(), b.y0() + b.height());
-    }
+    dump_svg_with_metadata(out, roots, cfg, &Metadata::default())
+}
+
+/// Like `dump_svg`, but with the given metadata describing how the packing was
+/// generated, see `metadata::load` to read it back.
+pub fn dump_svg_with_metadata<S: Shape, C: Packable>(
+    out: &mut impl Write,
+    roots: &[PackShape<S, C>],
+    cfg: &Settings,
+    metadata: &Metadata,
+) -> io::Result<()> {
+    let bbox = bounds(roots).ok_or_else(no_roots)?;
 
     writeln!(
         out,
@@ -83,33 +339,50 @@ pub fn dump_svg<S: Shape>(
         height = bbox.height(),
         color = cfg.palette[0],
     )?;
+    metadata.write(out, cfg)?;
 
     let mut stack = vec![];
 
     for root in roots {
-        root.write_svg(out, cfg.palette[root.color], "none")?;
+        root.write_svg(out, root.fill_color(cfg), "none")?;
         stack.extend(root.children());
     }
 
     while let Some(c) = stack.pop() {
-        c.write_svg(out, cfg.palette[c.color], "none")?;
+        c.write_svg(out, c.fill_color(cfg), "none")?;
         stack.extend(c.children());
     }
 
     writeln!(out, "</svg>")
 }
 
-impl<S: Shape> PackShape<S> {
+/// Bbox of all the given roots, `None` if there are none.
+pub(crate) fn bounds<S: Shape, C: Packable>(roots: &[PackShape<S, C>]) -> Option<Bbox> {
+    let mut bbox = roots.first()?.bbox();
+    for s in &roots[1..] {
+        bbox = bbox.union(&s.bbox());
+    }
+    Some(bbox)
+}
+
+/// Error of the outputs given no roots, whose size would be undefined.
+pub(crate) fn no_roots() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "no roots to write")
+}
+
+impl<S: Shape, C: Packable> PackShape<S, C> {
     pub fn new(shape: S) -> Self {
         Self {
             container: shape,
             children: vec![],
+            index: Grid::default(),
             occupied_area: 0.0,
             color: 0,
+            fill: None,
         }
     }
 
-    pub fn children(&self) -> &[PackShape<Circle>] {
+    pub fn children(&self) -> &[PackShape<C, C>] {
         &self.children
     }
 
@@ -117,29 +390,180 @@ impl<S: Shape> PackShape<S> {
         self.occupied_area
     }
 
-    pub fn pack(&mut self, mut shape: PackShape<Circle>, cfg: &Settings) -> bool {
-        for c in self.children.iter_mut() {
-            let (x, y) = shape.center();
-            let d = c.sdf(x, y);
+    /// Color this shape should be drawn with.
+    pub fn fill_color<'a>(&'a self, cfg: &'a Settings) -> &'a str {
+        self.fill.as_deref().unwrap_or(&cfg.palette[self.color])
+    }
 
-            if cfg.inside && d < -cfg.padding {
-                shape.set_radius(-d - cfg.padding);
-                shape.color = (shape.color + 1) % cfg.palette.len();
-                return c.pack(shape, cfg);
-            }
+    pub fn pack(&mut self, shape: PackShape<C, C>, cfg: &Settings) -> bool {
+        self.place(shape, cfg).is_some()
+    }
+
+    /// Like `pack`, but return the shape as it was placed, possibly nested in
+    /// one of the children and shrunk to fit.
+    pub fn place(
+        &mut self,
+        mut shape: PackShape<C, C>,
+        cfg: &Settings,
+    ) -> Option<&PackShape<C, C>> {
+        let (x, y) = shape.center();
+
+        if let Some((i, d)) = self.enclosing_child(x, y, cfg) {
+            shape.set_scale(shape.scale().min(-d - cfg.padding));
+            shape.color = (shape.color + 1) % cfg.palette.len();
+            return self.children[i].place(shape, cfg);
+        }
+
+        let radius = self.free_radius(x, y, shape.scale(), cfg)?;
+        shape.set_scale(self.grow(&shape.container, radius, cfg));
+
+        self.occupied_area += shape.area();
+        self.index
+            .insert(shape.bbox(), &self.container.bbox(), 2.0 * cfg.min_radius);
+        self.children.push(shape);
+        self.children.last()
+    }
+
+    /// Radius of the circle centered at the given point that `place` would
+    /// pack for a shape of at most the given scale, without packing it. It's
+    /// the scale of the shape for circles and a lower bound otherwise.
+    pub fn fit(&self, x: f32, y: f32, scale: f32, cfg: &Settings) -> Option<f32> {
+        match self.enclosing_child(x, y, cfg) {
+            Some((i, d)) => self.children[i].fit(x, y, scale.min(-d - cfg.padding), cfg),
+            None => self.free_radius(x, y, scale, cfg),
+        }
+    }
+
+    /// The child the given point is inside of, farther than the padding from
+    /// its boundary, together with the sdf of the child at that point.
+    fn enclosing_child(&self, x: f32, y: f32, cfg: &Settings) -> Option<(usize, f32)> {
+        if !cfg.inside {
+            return None;
+        }
+
+        let children = &self.children;
+        self.index
+            .candidates(x, y)
+            .iter()
+            .map(|&i| (i, children[i].sdf(x, y)))
+            .find(|&(_, d)| d < -cfg.padding)
+    }
+
+    /// Radius of the biggest circle centered at the given point, not bigger
+    /// than `radius`, that keeps the padding from all the children or `None`
+    /// if it's smaller than the minimum radius.
+    fn free_radius(&self, x: f32, y: f32, radius: f32, cfg: &Settings) -> Option<f32> {
+        let radius = self.shrink_to_children(x, y, radius, cfg);
+
+        if radius < cfg.min_radius {
+            None
+        } else {
+            Some(radius)
+        }
+    }
+
+    /// Radius of the biggest circle centered at the given point that keeps the
+    /// padding from the container and all the children, it's only exact when
+    /// positive.
+    fn clearance(&self, x: f32, y: f32, cfg: &Settings) -> f32 {
+        let radius = -self.container.sdf(x, y) - cfg.padding;
+        if radius <= 0.0 {
+            return radius;
+        }
+
+        self.shrink_to_children(x, y, radius, cfg)
+    }
 
-            if d - cfg.padding < shape.get_radius() {
-                shape.set_radius(d - cfg.padding);
+    /// Shrink the given radius of a circle centered at the given point until
+    /// it keeps the padding from all the children.
+    fn shrink_to_children(&self, x: f32, y: f32, mut radius: f32, cfg: &Settings) -> f32 {
+        // only the children closer than radius + padding can shrink the circle
+        // and their sdf is never smaller than the distance to their bbox, hence
+        // the grid search finds the same radius as scanning every child.
+        let children = &self.children;
+        self.index.visit_nearby(x, y, radius + cfg.padding, |i| {
+            let d = children[i].sdf(x, y);
+            if d - cfg.padding < radius {
+                radius = d - cfg.padding;
             }
+            radius + cfg.padding
+        });
+
+        radius
+    }
+
+    /// Find the biggest scale at which the given shape fits among the children
+    /// knowing that the circle of the given radius around its center does.
+    fn grow(&self, shape: &C, radius: f32, cfg: &Settings) -> f32 {
+        const STEPS: usize = 12;
+
+        // the shape always contains the circle of radius scale therefore it
+        // cannot be bigger than radius, but it's also contained in the circle
+        // of radius scale * outer_ratio which means that it always fits at
+        // radius / outer_ratio.
+        let ratio = shape.outer_ratio();
+        if ratio <= 1.0 {
+            return radius;
         }
 
-        if shape.get_radius() >= cfg.min_radius {
-            self.occupied_area += shape.area();
-            self.children.push(shape);
-            return true;
+        let (x, y) = shape.center();
+        let (mut lo, mut hi) = (radius / ratio, radius);
+        for _ in 0..STEPS {
+            let mid = (lo + hi) / 2.0;
+            if self.fits(&shape.placed(x, y, mid), cfg) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
         }
 
-        false
+        lo
+    }
+
+    fn fits(&self, shape: &C, cfg: &Settings) -> bool {
+        let children = &self.children;
+
+        // children completely inside the shape are not caught by the outline
+        // check, but their center must be inside the shape.
+        let (x, y) = shape.center();
+        let reach = shape.scale() * shape.outer_ratio();
+        let mut fits = true;
+        self.index.visit_nearby(x, y, reach, |i| {
+            let (cx, cy) = children[i].center();
+            if shape.sdf(cx, cy) < 0.0 {
+                fits = false;
+                return f32::NEG_INFINITY;
+            }
+            reach
+        });
+
+        let tolerance = (cfg.min_radius / 2.0).max(shape.scale() / 64.0);
+        fits && shape.outline(tolerance).into_iter().all(|(x, y)| {
+            if self.sdf(x, y) > -cfg.padding {
+                return false;
+            }
+
+            let mut fits = true;
+            self.index.visit_nearby(x, y, cfg.padding, |i| {
+                if children[i].sdf(x, y) < cfg.padding {
+                    fits = false;
+                    return f32::NEG_INFINITY;
+                }
+                cfg.padding
+            });
+            fits
+        })
+    }
+}
+
+impl<C: Packable> PackShape<C, C> {
+    pub fn scale(&self) -> f32 {
+        self.container.scale()
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        let (x, y) = self.center();
+        self.container = self.container.placed(x, y, scale);
     }
 }
 
@@ -157,7 +581,7 @@ impl PackShape<Circle> {
     }
 }
 
-impl<S: Shape> Shape for PackShape<S> {
+impl<S: Shape, C: Packable> Shape for PackShape<S, C> {
     fn bbox(&self) -> Bbox {
         self.container.bbox()
     }
@@ -176,4 +600,10 @@ impl<S: Shape> Shape for PackShape<S> {
     fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
         self.container.write_svg(w, fill, stroke)
     }
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.container.write_pdf(w)
+    }
+    fn strokes(&self, tolerance: f32) -> Vec<plot::Stroke> {
+        self.container.strokes(tolerance)
+    }
 }
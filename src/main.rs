@@ -1,12 +1,36 @@
-use std::{fs::File, io::BufWriter, path::PathBuf};
+use std::{
+    fs::File,
+    io::{self, BufWriter, IsTerminal},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
 
+use image::DynamicImage;
 use rand::prelude::*;
-use structopt::StructOpt;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use structopt::{clap::AppSettings, StructOpt};
 
-use circle_packing::{self, Bbox, PackShape, Settings};
+use circle_packing::{
+    self,
+    halftone::RadiusField,
+    metadata::{self, Metadata},
+    paint::{self, Sampling},
+    pdf::{self, Page},
+    placement::Placement,
+    plot::{self, Fill, Grouping, PlotSettings},
+    progress::{Observer, Progress},
+    raster,
+    sampler::CandidateSampler,
+    scene::{Container, Scene},
+    svg,
+    tree::{self, Packing},
+    Bbox, Circle, Mask, PackShape, Packable, Polyline, RegularPolygon, Settings, Shape,
+    StopCriterion,
+};
 
-type PALETTE = (&'static str, &'static [&'static str]);
-static PALETTES: &'static [PALETTE] = &[
+type Palette = (&'static str, &'static [&'static str]);
+static PALETTES: &[Palette] = &[
     //
     // duo
     //
@@ -38,11 +62,21 @@ static PALETTES: &'static [PALETTE] = &[
 
 /// Program to create some SVG images from random circle packing runs.
 #[derive(Debug, StructOpt)]
+#[structopt(global_setting = AppSettings::AllArgsOverrideSelf)]
 pub struct App {
+    #[structopt(subcommand)]
+    command: Option<Command>,
+
     /// Show available themes and exit.
     #[structopt(long)]
     list_themes: bool,
 
+    /// Seed of the random number generator, the same seed with the same
+    /// options always produces the same output. A random one is used if
+    /// missing.
+    #[structopt(long)]
+    seed: Option<u64>,
+
     /// Padding between the circles.
     #[structopt(short, long, default_value = "5.0")]
     padding: f32,
@@ -56,11 +90,48 @@ pub struct App {
     #[structopt(long, default_value = "0.8")]
     target_coverage: f32,
 
+    /// Number of attempts in a row that fail to place a shape after which the
+    /// packing stops.
+    #[structopt(long, default_value = "1000")]
+    max_stall: usize,
+
+    /// When to stop packing instead of --target-coverage and --max-stall:
+    /// placed:<shapes>, coverage:<fraction>, attempts:<n>, stall:<n>,
+    /// time:<seconds>, acceptance_rate:<fraction> or any(...) and all(...) of
+    /// them, for example any(placed:500,time:10). It overrides the scene.
+    #[structopt(long)]
+    stop: Option<StopCriterion>,
+
+    /// How the point where each shape is placed is chosen, either first to
+    /// take the first random point where a shape fits,
+    /// best-of:<k>[:<k when full>] to take the best of k random points, which
+    /// places the biggest shapes first, or largest-empty to always take the
+    /// biggest gap left.
+    #[structopt(long, default_value = "first")]
+    placement: Placement,
+
+    /// Where the points tried by --placement come from, either uniform,
+    /// halton, sobol, jitter[:<spacing>] or poisson[:<radius>]. All but
+    /// uniform spread the points more evenly.
+    #[structopt(long, default_value = "uniform")]
+    sampler: CandidateSampler,
+
+    /// Stop packing each container after this many seconds, keeping the shapes
+    /// placed so far. Shorthand for adding time:<seconds> to --stop with
+    /// any(...), it applies to scenes too.
+    #[structopt(long)]
+    time_limit: Option<f32>,
+
     /// Whether circles can contain other non-intersecting circles. This does
     /// not affect the total area covered by circles.
     #[structopt(long)]
     no_inside: bool,
 
+    /// Pack regular polygons with this many sides instead of circles, for
+    /// example 4 for squares and 6 for hexagons.
+    #[structopt(long)]
+    sides: Option<u32>,
+
     /// Theme to use when saving the final image.
     #[structopt(short, long)]
     theme: Option<String>,
@@ -73,15 +144,158 @@ pub struct App {
     #[structopt(short, long, default_value = "1080")]
     height: u16,
 
-    /// Path where to save the image at.
+    /// SVG file whose shapes are packed instead of a width x height
+    /// rectangle. PNG, JPEG and PNM images are also supported, their pixels
+    /// brighter than the threshold are packed.
+    #[structopt(short, long)]
+    container: Option<PathBuf>,
+
+    /// TOML or JSON scene describing the containers, the settings, the seed
+    /// and the outputs. It replaces the options for those, except for --seed
+    /// and --theme which override the scene.
+    #[structopt(long)]
+    scene: Option<PathBuf>,
+
+    /// Tree saved with --save-tree to render instead of packing again. Only
+    /// the options about the outputs, the theme and the image apply.
+    #[structopt(long)]
+    load_tree: Option<PathBuf>,
+
+    /// Path where to save the packed tree, so that it can be rendered again
+    /// with --load-tree. It's saved as JSON if it ends with .json and in a
+    /// compact binary format otherwise.
+    #[structopt(long)]
+    save_tree: Option<PathBuf>,
+
+    /// Luminance in [0, 255] above which the pixels of an image container
+    /// are inside.
+    #[structopt(long, default_value = "127")]
+    threshold: u8,
+
+    /// Image whose colors are used to fill the shapes instead of the theme,
+    /// stretched to cover the whole output.
+    #[structopt(long)]
+    image: Option<PathBuf>,
+
+    /// How the color of a shape is taken from the image: "center" uses the
+    /// pixel under its center while "mean" averages the pixels it covers.
+    #[structopt(long, default_value = "mean")]
+    sampling: Sampling,
+
+    /// Image whose luminance limits the size of the shapes at each point,
+    /// stretched to cover the whole output. Bright areas get the biggest
+    /// shapes.
+    #[structopt(long)]
+    halftone: Option<PathBuf>,
+
+    /// Radius of the shapes in the brightest areas of the halftone image.
+    #[structopt(long, default_value = "40.0")]
+    max_radius: f32,
+
+    /// Give the biggest shapes to the darkest areas of the halftone image.
+    #[structopt(long)]
+    invert: bool,
+
+    /// Path where to save the image at, it's rendered as a PNG or a PDF if it
+    /// ends with .png or .pdf, as plotter strokes if it ends with .hpgl, .plt
+    /// or .gcode and as an SVG otherwise.
     #[structopt(short, long, default_value = "packing.svg")]
     output: PathBuf,
+
+    /// Pixels per unit of the PNG output.
+    #[structopt(long, default_value = "1.0")]
+    scale: f32,
+
+    /// Number of samples per side of each pixel used to anti-alias the PNG
+    /// output.
+    #[structopt(long, default_value = "4")]
+    supersampling: u32,
+
+    /// Page size of the PDF output, either a4, a3, letter or <width>x<height>
+    /// in millimeters.
+    #[structopt(long, default_value = "a4")]
+    page: Page,
+
+    /// Margin around the drawing in the PDF output, in millimeters.
+    #[structopt(long, default_value = "10.0")]
+    margin: f32,
+
+    /// Use the PDF page in landscape orientation.
+    #[structopt(long)]
+    landscape: bool,
+
+    /// Millimeters per unit of the plotter output.
+    #[structopt(long, default_value = "0.1")]
+    plot_scale: f32,
+
+    /// Fill of the shapes in the plotter output, either none,
+    /// hatch:<spacing>[:<angle in degrees>] or concentric:<spacing>.
+    #[structopt(long, default_value = "none")]
+    fill: Fill,
+
+    /// How the strokes of the plotter output are split between pens, either
+    /// color, depth or none.
+    #[structopt(long, default_value = "color")]
+    grouping: Grouping,
+
+    /// Draw the strokes of the plotter output in the same order of the SVG
+    /// instead of reordering them to shorten the travel of the pen.
+    #[structopt(long)]
+    keep_order: bool,
+
+    /// G-code command to lift the pen.
+    #[structopt(long, default_value = "G0 Z5")]
+    pen_up: String,
+
+    /// G-code command to lower the pen.
+    #[structopt(long, default_value = "G1 Z0")]
+    pen_down: String,
+
+    /// G-code command to pause for a pen change before each color.
+    #[structopt(long, default_value = "M0")]
+    pen_change: String,
+
+    /// Speed of the pen while drawing, in millimeters per minute.
+    #[structopt(long, default_value = "1500")]
+    feed_rate: f32,
+
+    /// Speed of the pen while moving between strokes, in millimeters per
+    /// minute.
+    #[structopt(long, default_value = "3000")]
+    travel_rate: f32,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Pack again with the options stored in an SVG written by this program.
+    /// Options given after the path override the stored ones, for example
+    /// `regenerate packing.svg --theme dt02 --output other.svg`.
+    #[structopt(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+    Regenerate { svg: PathBuf, args: Vec<String> },
 }
 
 fn main() {
-    let mut rng = thread_rng();
+    run(std::env::args().skip(1).collect());
+}
+
+/// Options that only choose where the results are written, left out of the
+/// metadata so that it's the same wherever they're written.
+const OUTPUT_OPTIONS: &[&str] = &["-o", "--output", "--save-tree"];
+
+/// Run the program with the given command line arguments, without the name
+/// of the program.
+fn run(args: Vec<String>) {
+    let app = App::from_iter(std::iter::once("circle-packing".to_string()).chain(args.clone()));
 
-    let app = App::from_args();
+    if let Some(Command::Regenerate {
+        svg,
+        args: overrides,
+    }) = &app.command
+    {
+        return regenerate(svg, overrides);
+    }
+
+    let mut args = without_output_options(args);
 
     if app.list_themes {
         println!("Available themes");
@@ -93,43 +307,532 @@ fn main() {
         return;
     }
 
+    if let Some(path) = &app.load_tree {
+        return load_tree(path, &app, args);
+    }
+
+    let scene = app.scene.as_ref().map(|path| {
+        Scene::load(path).unwrap_or_else(|err| {
+            eprintln!("cannot load {}: {}", path.display(), err);
+            std::process::exit(1);
+        })
+    });
+
+    let seed = app
+        .seed
+        .or_else(|| scene.as_ref().and_then(|s| s.seed))
+        .unwrap_or_else(|| thread_rng().gen());
+    println!("using seed {}", seed);
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    if app.seed.is_none() {
+        args.extend(["--seed".to_string(), seed.to_string()]);
+    }
+
+    // always draw the random theme so that picking a theme doesn't change the
+    // rest of the packing
+    let random_theme = PALETTES.choose(&mut rng);
     let (theme_name, palette) = app
         .theme
+        .as_deref()
         .and_then(|t| {
-            let theme = PALETTES.iter().find(|(n, _)| n == &&t);
+            let theme = PALETTES.iter().find(|(n, _)| *n == t);
             if theme.is_none() {
                 println!("theme {} not found, using a random one", t);
             }
             theme
         })
-        .or_else(|| PALETTES.choose(&mut rng))
+        .or(random_theme)
         .unwrap();
 
-    println!("using theme {}", theme_name);
+    let palette = palette.iter().map(|c| c.to_string()).collect();
 
-    let settings = Settings {
-        min_radius: app.min_radius,
-        padding: app.padding,
-        inside: !app.no_inside,
-        palette,
-        target_area: app.target_coverage,
-        max_stall_iterations: 1000,
+    let mut settings = match &scene {
+        Some(scene) if app.theme.is_none() => scene.settings.clone(),
+        Some(scene) => Settings {
+            palette,
+            ..scene.settings.clone()
+        },
+        None => Settings {
+            min_radius: app.min_radius,
+            padding: app.padding,
+            inside: !app.no_inside,
+            palette,
+            target_area: app.target_coverage,
+            max_stall_iterations: app.max_stall,
+            stop: None,
+            placement: app.placement,
+            sampler: app.sampler,
+        },
     };
+    if app.stop.is_some() {
+        settings.stop = app.stop.clone();
+    }
+    if let Some(t) = app.time_limit {
+        settings.stop = Some(StopCriterion::Any(vec![
+            settings.stop_criterion(),
+            StopCriterion::Time(t),
+        ]));
+    }
+
+    if scene.is_none() || app.theme.is_some() {
+        println!("using theme {}", theme_name);
+    }
 
     assert!(settings.padding >= 0.0);
+    assert!(app.sides.is_none_or(|s| s >= 3));
 
-    let container = {
-        let mut b = Bbox::new(0.0, 0.0);
-        b.expand(app.width.into(), app.height.into());
-        b
+    let metadata = Metadata {
+        seed: Some(seed),
+        container: Some(match (&app.scene, &app.container) {
+            (Some(path), _) => format!("scene {}", path.display()),
+            (None, None) => format!("{}x{} rectangle", app.width, app.height),
+            (None, Some(path)) => path.display().to_string(),
+        }),
+        args,
+        ..Metadata::default()
     };
 
-    let mut root = PackShape::new(container);
-    root.color = 1 % settings.palette.len();
+    let outputs = match &scene {
+        Some(scene) if !scene.outputs.is_empty() => scene.outputs.clone(),
+        _ => vec![app.output.clone()],
+    };
+    let ctx = Context {
+        app: &app,
+        settings: &settings,
+        metadata: &metadata,
+        outputs: &outputs,
+    };
 
-    circle_packing::pack(&mut root, &settings, &mut rng);
+    if let Some(scene) = &scene {
+        let mut containers = vec![];
+        for container in &scene.containers {
+            let shapes = container.shapes().unwrap_or_else(|err| {
+                eprintln!("cannot load scene: {}", err);
+                std::process::exit(1);
+            });
+            containers.extend(shapes.into_iter().map(|s| (s, Some(container))));
+        }
+
+        return pack_containers(containers, &ctx, &mut rng);
+    }
 
-    let f = File::create(app.output).unwrap();
+    match &app.container {
+        None => {
+            let mut b = Bbox::new(0.0, 0.0);
+            b.expand(app.width.into(), app.height.into());
+
+            pack_containers(vec![(b, None)], &ctx, &mut rng)
+        }
+        Some(path) if path.extension().is_some_and(|e| e == "svg") => {
+            let containers = svg::load(path, 0.5).unwrap_or_else(|err| {
+                eprintln!("cannot load {}: {}", path.display(), err);
+                std::process::exit(1);
+            });
+
+            let containers = containers.into_iter().map(|c| (c, None)).collect();
+            pack_containers(containers, &ctx, &mut rng)
+        }
+        Some(path) => {
+            let mask = Mask::load(path, app.threshold).unwrap_or_else(|err| {
+                eprintln!("cannot load {}: {}", path.display(), err);
+                std::process::exit(1);
+            });
+
+            pack_containers(vec![(mask, None)], &ctx, &mut rng)
+        }
+    }
+}
+
+/// Run the program again with the arguments stored in the metadata of the
+/// given SVG followed by `overrides`.
+fn regenerate(svg: &Path, overrides: &[String]) {
+    let metadata = metadata::load(svg).unwrap_or_else(|err| {
+        eprintln!("cannot load {}: {}", svg.display(), err);
+        std::process::exit(1);
+    });
+
+    if metadata.version != env!("CARGO_PKG_VERSION") {
+        println!(
+            "{} was generated by version {}, the result might differ",
+            svg.display(),
+            metadata.version
+        );
+    }
+
+    // the output isn't stored, write to the same file unless overridden
+    let mut args = metadata.args;
+    args.extend(["--output".to_string(), svg.display().to_string()]);
+    args.extend(overrides.iter().cloned());
+
+    let app = App::from_iter(std::iter::once("circle-packing".to_string()).chain(args.clone()));
+    if !overrides.is_empty() && app.output == svg {
+        eprintln!(
+            "refusing to overwrite {} with a different packing, use --output",
+            svg.display()
+        );
+        std::process::exit(1);
+    }
+
+    run(args)
+}
+
+/// The given arguments without `OUTPUT_OPTIONS` and their values.
+fn without_output_options(args: Vec<String>) -> Vec<String> {
+    let mut kept = vec![];
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            kept.push(arg);
+            kept.extend(args.by_ref());
+            break;
+        }
+
+        let output = OUTPUT_OPTIONS.iter().find(|o| arg.starts_with(*o));
+        match output {
+            Some(o) if arg == *o => {
+                args.next();
+            }
+            // --output=<path>, or -o<path> for the short ones
+            Some(o) if o.len() == 2 || arg[o.len()..].starts_with('=') => {}
+            _ => kept.push(arg),
+        }
+    }
+
+    kept
+}
+
+/// Render the tree saved at the given path to the outputs, with the theme
+/// given on the command line or the palette it was packed with.
+fn load_tree(path: &Path, app: &App, args: Vec<String>) {
+    let header: TreeHeader = tree::load(path).unwrap_or_else(|err| {
+        eprintln!("cannot load {}: {}", path.display(), err);
+        std::process::exit(1);
+    });
+
+    match (header.container.as_str(), header.child.as_str()) {
+        ("rect", "circle") => render_tree::<Bbox, Circle>(path, app, args),
+        ("rect", "polygon") => render_tree::<Bbox, RegularPolygon>(path, app, args),
+        ("polyline", "circle") => render_tree::<Polyline, Circle>(path, app, args),
+        ("polyline", "polygon") => render_tree::<Polyline, RegularPolygon>(path, app, args),
+        ("mask", "circle") => render_tree::<Mask, Circle>(path, app, args),
+        ("mask", "polygon") => render_tree::<Mask, RegularPolygon>(path, app, args),
+        (container, child) => {
+            eprintln!(
+                "cannot load {}: unknown tree of {} in {}",
+                path.display(),
+                child,
+                container
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+fn render_tree<S, C>(path: &Path, app: &App, args: Vec<String>)
+where
+    S: Shape + Kind + for<'de> Deserialize<'de>,
+    C: Packable + Kind + for<'de> Deserialize<'de>,
+{
+    let file: TreeFile<Packing<S, C>> = tree::load(path).unwrap_or_else(|err| {
+        eprintln!("cannot load {}: {}", path.display(), err);
+        std::process::exit(1);
+    });
+    let Packing {
+        mut settings,
+        mut roots,
+    } = file.packing;
+
+    if let Some(t) = &app.theme {
+        match PALETTES.iter().find(|(n, _)| n == t) {
+            None => println!("theme {} not found, using the saved palette", t),
+            Some((_, palette)) => {
+                println!("using theme {}", t);
+                settings.palette = palette.iter().map(|c| c.to_string()).collect();
+            }
+        }
+    }
+
+    let metadata = Metadata {
+        container: Some(format!("tree {}", path.display())),
+        args,
+        ..Metadata::default()
+    };
+    let ctx = Context {
+        app,
+        settings: &settings,
+        metadata: &metadata,
+        outputs: std::slice::from_ref(&app.output),
+    };
+    if roots.is_empty() {
+        no_shapes(&metadata);
+    }
+
+    if let Some(image) = &app.image {
+        paint_all(&mut roots, &open_image(image).to_rgb8(), app);
+    }
+    for output in ctx.outputs {
+        save(&roots, output, &ctx);
+    }
+}
+
+/// Names of the shapes that can be saved with --save-tree, stored in the
+/// tree to know which shapes to load it as.
+trait Kind {
+    const KIND: &'static str;
+}
+
+impl Kind for Bbox {
+    const KIND: &'static str = "rect";
+}
+
+impl Kind for Polyline {
+    const KIND: &'static str = "polyline";
+}
+
+impl Kind for Mask {
+    const KIND: &'static str = "mask";
+}
+
+impl Kind for Circle {
+    const KIND: &'static str = "circle";
+}
+
+impl Kind for RegularPolygon {
+    const KIND: &'static str = "polygon";
+}
+
+/// Packing saved with --save-tree along with the kinds of its shapes.
+#[derive(Serialize, Deserialize)]
+struct TreeFile<P> {
+    container: String,
+    child: String,
+    packing: P,
+}
+
+/// Leading fields of `TreeFile`, read before the packing to know its type.
+#[derive(Deserialize)]
+struct TreeHeader {
+    container: String,
+    child: String,
+}
+
+/// Everything needed to pack and save besides the containers.
+struct Context<'a> {
+    app: &'a App,
+    settings: &'a Settings,
+    metadata: &'a Metadata,
+    outputs: &'a [PathBuf],
+}
+
+/// Containers to pack, with the entry of the scene they come from if any.
+type Containers<'a, S> = Vec<(S, Option<&'a Container>)>;
+
+fn pack_containers<S>(containers: Containers<S>, ctx: &Context, rng: &mut impl Rng)
+where
+    S: Shape + Kind + Serialize,
+{
+    match ctx.app.sides {
+        None => pack_and_save(containers, &Circle::new(0.0, 0.0, 1.0), ctx, rng),
+        Some(sides) => pack_and_save(
+            containers,
+            &RegularPolygon::new(0.0, 0.0, 1.0, sides),
+            ctx,
+            rng,
+        ),
+    }
+}
+
+fn pack_and_save<S, C>(containers: Containers<S>, child: &C, ctx: &Context, rng: &mut impl Rng)
+where
+    S: Shape + Kind + Serialize,
+    C: Packable + Kind + Serialize,
+{
+    let (app, settings) = (ctx.app, ctx.settings);
+    if containers.is_empty() {
+        no_shapes(ctx.metadata);
+    }
+    let image = app.image.as_ref().map(|path| open_image(path).to_rgb8());
+
+    let mut area = containers[0].0.bbox();
+    for (container, _) in &containers[1..] {
+        area = area.union(&container.bbox());
+    }
+
+    let field = app.halftone.as_ref().map(|path| {
+        let mut field = RadiusField::new(
+            &open_image(path),
+            area.clone(),
+            settings.min_radius,
+            app.max_radius,
+        );
+        field.invert = app.invert;
+        field
+    });
+
+    let mut status = Status {
+        verbose: io::stderr().is_terminal(),
+    };
+
+    let mut roots = vec![];
+    for (container, entry) in containers {
+        let mut root = PackShape::new(container);
+        match entry {
+            None => root.color = 1 % settings.palette.len(),
+            Some(entry) => entry.style(&mut root, settings),
+        }
+
+        let max_scale = |x, y| match &field {
+            None => f32::INFINITY,
+            Some(field) => field.radius(x, y),
+        };
+        let progress = circle_packing::pack_with_observer(
+            &mut root,
+            child,
+            settings,
+            max_scale,
+            &mut status,
+            rng,
+        );
+        if status.verbose {
+            status.show(&progress);
+            eprintln!();
+        }
+        if let Some(entry) = entry {
+            entry.apply_palette(&mut root);
+        }
+        roots.push(root);
+    }
+
+    if let Some(image) = &image {
+        paint_all(&mut roots, image, app);
+    }
+
+    if let Some(path) = &app.save_tree {
+        let file = TreeFile {
+            container: S::KIND.to_string(),
+            child: C::KIND.to_string(),
+            packing: Packing {
+                settings: settings.clone(),
+                roots,
+            },
+        };
+        tree::save(path, &file).unwrap_or_else(|err| {
+            eprintln!("cannot save {}: {}", path.display(), err);
+            std::process::exit(1);
+        });
+        roots = file.packing.roots;
+    }
+
+    for output in ctx.outputs {
+        save(&roots, output, ctx);
+    }
+}
+
+/// Observer showing the progress of the packing on the terminal.
+struct Status {
+    verbose: bool,
+}
+
+impl Status {
+    fn show(&self, progress: &Progress) {
+        eprint!(
+            "\r{} shapes, {:.1}% covered, {} attempts in {:.1}s",
+            progress.placed,
+            100.0 * progress.coverage,
+            progress.attempts,
+            progress.elapsed.as_secs_f32()
+        );
+    }
+}
+
+impl<C: Packable> Observer<C> for Status {
+    fn progress(&mut self, progress: &Progress) -> ControlFlow<()> {
+        if self.verbose {
+            self.show(progress);
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+/// Fill the shapes of all the roots with the colors of the image, stretched
+/// over all of them.
+fn paint_all<S: Shape, C: Packable>(
+    roots: &mut [PackShape<S, C>],
+    image: &image::RgbImage,
+    app: &App,
+) {
+    let mut area = roots[0].bbox();
+    for root in &roots[1..] {
+        area = area.union(&root.bbox());
+    }
+
+    for root in roots {
+        paint::paint(root, image, &area, app.sampling);
+    }
+}
+
+fn save<S: Shape, C: Packable>(roots: &[PackShape<S, C>], output: &Path, ctx: &Context) {
+    let (app, settings) = (ctx.app, ctx.settings);
+
+    let f = File::create(output).unwrap_or_else(|err| {
+        eprintln!("cannot create {}: {}", output.display(), err);
+        std::process::exit(1);
+    });
     let mut bf = BufWriter::new(f);
-    circle_packing::dump_svg(&mut bf, &[root], &settings).unwrap();
+    if output.extension().is_some_and(|e| e == "png") {
+        raster::dump_png(&mut bf, roots, settings, app.scale, app.supersampling).unwrap();
+    } else if output.extension().is_some_and(|e| e == "pdf") {
+        let mut page = Page {
+            margin: app.margin,
+            ..app.page.clone()
+        };
+        if app.landscape {
+            page = page.landscape();
+        }
+
+        pdf::dump_pdf(&mut bf, roots, settings, &page).unwrap();
+    } else if let Some(ext) = output
+        .extension()
+        .filter(|e| *e == "hpgl" || *e == "plt" || *e == "gcode")
+    {
+        let plot_settings = PlotSettings {
+            scale: app.plot_scale,
+            fill: app.fill.clone(),
+            grouping: app.grouping,
+            optimize_travel: !app.keep_order,
+            pen_up: app.pen_up.clone(),
+            pen_down: app.pen_down.clone(),
+            pen_change: app.pen_change.clone(),
+            feed_rate: app.feed_rate,
+            travel_rate: app.travel_rate,
+            ..PlotSettings::default()
+        };
+
+        if ext == "gcode" {
+            plot::dump_gcode(&mut bf, roots, settings, &plot_settings).unwrap();
+        } else {
+            plot::dump_hpgl(&mut bf, roots, settings, &plot_settings).unwrap();
+        }
+    } else {
+        circle_packing::dump_svg_with_metadata(&mut bf, roots, settings, ctx.metadata).unwrap();
+    }
+}
+
+/// Exit when there's nothing to pack or draw in the containers described by
+/// the given metadata.
+fn no_shapes(metadata: &Metadata) -> ! {
+    eprintln!(
+        "no shapes found in {}",
+        metadata.container.as_deref().unwrap_or("the containers")
+    );
+    std::process::exit(1);
+}
+
+fn open_image(path: &Path) -> DynamicImage {
+    image::open(path).unwrap_or_else(|err| {
+        eprintln!("cannot load {}: {}", path.display(), err);
+        std::process::exit(1);
+    })
 }
@@ -0,0 +1,294 @@
+//! Containers from raster silhouettes.
+
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Write},
+    path::Path,
+};
+
+use image::{DynamicImage, ImageError};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{contour, Bbox, Shape};
+
+/// Squared distance used for pixels with no feature in range.
+const FAR: f32 = 1e20;
+
+/// Shape made of the pixels of a bitmap, one unit per pixel with the top left
+/// corner of the image at the origin.
+///
+/// The sdf is precomputed with an exact euclidean distance transform of the
+/// pixel centers and is bilinearly interpolated between them, so it's only
+/// accurate up to about half a pixel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mask {
+    width: usize,
+    height: usize,
+
+    /// Signed distance at the center of each pixel, with a ring of outside
+    /// pixels around the image.
+    field: Vec<f32>,
+
+    /// Indices of the inside pixels, used for sampling.
+    inside: Vec<usize>,
+
+    bbox: Bbox,
+    center: (f32, f32),
+}
+
+#[derive(Debug)]
+pub enum MaskError {
+    Image(ImageError),
+
+    /// No pixel of the image is brighter than the threshold, or it has none.
+    Empty {
+        threshold: u8,
+    },
+}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaskError::Image(e) => write!(f, "{}", e),
+            MaskError::Empty { threshold } => {
+                write!(f, "no pixel is brighter than the threshold {}", threshold)
+            }
+        }
+    }
+}
+
+impl Error for MaskError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MaskError::Image(e) => Some(e),
+            MaskError::Empty { .. } => None,
+        }
+    }
+}
+
+impl From<ImageError> for MaskError {
+    fn from(e: ImageError) -> Self {
+        MaskError::Image(e)
+    }
+}
+
+impl Mask {
+    /// Read the image at the given path, see `from_image`.
+    pub fn load(path: impl AsRef<Path>, threshold: u8) -> Result<Self, MaskError> {
+        Self::from_image(&image::open(path)?, threshold)
+    }
+
+    /// Build a mask whose inside pixels are the ones with a luminance greater
+    /// than `threshold`, there must be at least one.
+    pub fn from_image(img: &DynamicImage, threshold: u8) -> Result<Self, MaskError> {
+        let img = img.to_luma8();
+        let (width, height) = img.dimensions();
+
+        let inside = img.pixels().map(|p| p.0[0] > threshold).collect::<Vec<_>>();
+        if !inside.contains(&true) {
+            return Err(MaskError::Empty { threshold });
+        }
+
+        Ok(Self::new(width as usize, height as usize, inside))
+    }
+
+    /// Build a mask of `width` by `height` pixels from the row major list of
+    /// which pixels are inside. Random points can only be drawn from masks
+    /// with some inside pixel.
+    pub fn new(width: usize, height: usize, inside: Vec<bool>) -> Self {
+        assert_eq!(inside.len(), width * height);
+
+        let (pw, ph) = (width + 2, height + 2);
+        let mut padded = vec![false; pw * ph];
+        for y in 0..height {
+            padded[(y + 1) * pw + 1..(y + 1) * pw + 1 + width]
+                .copy_from_slice(&inside[y * width..(y + 1) * width]);
+        }
+
+        let to_inside = distance_transform(pw, ph, &padded);
+        let outside = padded.iter().map(|i| !i).collect::<Vec<_>>();
+        let to_outside = distance_transform(pw, ph, &outside);
+
+        // the boundary lies halfway between an inside and an outside pixel
+        let field = padded
+            .iter()
+            .enumerate()
+            .map(|(i, &inside)| {
+                if inside {
+                    0.5 - to_outside[i].sqrt()
+                } else {
+                    to_inside[i].sqrt() - 0.5
+                }
+            })
+            .collect();
+
+        let inside = (0..width * height)
+            .filter(|&i| inside[i])
+            .collect::<Vec<_>>();
+
+        let mut bbox = Bbox::new(0.0, 0.0);
+        let (mut cx, mut cy) = (0.0, 0.0);
+        for (n, &i) in inside.iter().enumerate() {
+            let (x, y) = ((i % width) as f32, (i / width) as f32);
+            if n == 0 {
+                bbox = Bbox::new(x, y);
+            }
+            bbox.expand(x, y);
+            bbox.expand(x + 1.0, y + 1.0);
+
+            cx += x + 0.5;
+            cy += y + 0.5;
+        }
+        if !inside.is_empty() {
+            cx /= inside.len() as f32;
+            cy /= inside.len() as f32;
+        }
+
+        Self {
+            width,
+            height,
+            field,
+            inside,
+            bbox,
+            center: (cx, cy),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl Shape for Mask {
+    fn bbox(&self) -> Bbox {
+        self.bbox.clone()
+    }
+
+    fn center(&self) -> (f32, f32) {
+        self.center
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        let (pw, ph) = (self.width + 2, self.height + 2);
+
+        // position in the padded field where pixel centers are at integers
+        let fx = x + 0.5;
+        let fy = y + 0.5;
+        let cx = fx.clamp(0.0, (pw - 1) as f32);
+        let cy = fy.clamp(0.0, (ph - 1) as f32);
+
+        let extra = (fx - cx).hypot(fy - cy);
+
+        let (i, j) = ((cx as usize).min(pw - 2), (cy as usize).min(ph - 2));
+        let (tx, ty) = (cx - i as f32, cy - j as f32);
+        let v = |i: usize, j: usize| self.field[j * pw + i];
+
+        let top = v(i, j) * (1.0 - tx) + v(i + 1, j) * tx;
+        let bottom = v(i, j + 1) * (1.0 - tx) + v(i + 1, j + 1) * tx;
+        let d = top * (1.0 - ty) + bottom * ty;
+
+        if extra > 0.0 {
+            // outside of the image, both are lower bounds of the distance
+            (d - extra).max(self.bbox.sdf(x, y))
+        } else {
+            d
+        }
+    }
+
+    fn area(&self) -> f32 {
+        self.inside.len() as f32
+    }
+
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        let i = *self.inside.choose(rng).unwrap();
+        (
+            (i % self.width) as f32 + rng.gen::<f32>(),
+            (i / self.width) as f32 + rng.gen::<f32>(),
+        )
+    }
+
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        writeln!(
+            w,
+            r#"<path d="{}" fill="{}" stroke="{}" fill-rule="evenodd"/>"#,
+            contour::svg_path(&contour::contours(self, 1.0)),
+            fill,
+            stroke
+        )
+    }
+}
+
+/// Squared euclidean distance from the center of each pixel to the center of
+/// the closest pixel that is set, see "Distance Transforms of Sampled
+/// Functions" by Felzenszwalb and Huttenlocher.
+fn distance_transform(width: usize, height: usize, set: &[bool]) -> Vec<f32> {
+    let mut d = set
+        .iter()
+        .map(|&s| if s { 0.0 } else { FAR })
+        .collect::<Vec<_>>();
+
+    let n = width.max(height);
+    let mut f = vec![0.0; n];
+    let mut out = vec![0.0; n];
+    let mut v = vec![0; n];
+    let mut z = vec![0.0; n + 1];
+
+    for x in 0..width {
+        for y in 0..height {
+            f[y] = d[y * width + x];
+        }
+        distance_transform_1d(&f[..height], &mut out, &mut v, &mut z);
+        for y in 0..height {
+            d[y * width + x] = out[y];
+        }
+    }
+
+    for y in 0..height {
+        f[..width].copy_from_slice(&d[y * width..(y + 1) * width]);
+        distance_transform_1d(&f[..width], &mut out, &mut v, &mut z);
+        d[y * width..(y + 1) * width].copy_from_slice(&out[..width]);
+    }
+
+    d
+}
+
+/// Lower envelope of the parabolas rooted at each sample of `f`.
+fn distance_transform_1d(f: &[f32], out: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    let n = f.len();
+    let intersection = |q: usize, p: usize| {
+        ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2 * (q - p)) as f32
+    };
+
+    let mut k = 0;
+    v[0] = 0;
+    z[0] = f32::NEG_INFINITY;
+    z[1] = f32::INFINITY;
+
+    for q in 1..n {
+        let mut s = intersection(q, v[k]);
+        while s <= z[k] {
+            k -= 1;
+            s = intersection(q, v[k]);
+        }
+
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = f32::INFINITY;
+    }
+
+    k = 0;
+    for (q, o) in out.iter_mut().enumerate().take(n) {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let dq = q as f32 - v[k] as f32;
+        *o = dq * dq + f[v[k]];
+    }
+}
@@ -0,0 +1,146 @@
+//! Description of how a packing was generated, embedded in the `<metadata>`
+//! of the SVG output so that the packing can be reproduced later.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{svg::SvgError, CandidateSampler, Placement, Settings};
+
+/// Namespace of the elements written inside `<metadata>`.
+const NAMESPACE: &str = "urn:circle-packing";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// Version of the crate that generated the packing.
+    pub version: String,
+
+    /// Seed of the random number generator used for the packing.
+    pub seed: Option<u64>,
+
+    /// Human readable description of the containers.
+    pub container: Option<String>,
+
+    /// Command line arguments that reproduce the packing, without the name of
+    /// the program.
+    pub args: Vec<String>,
+}
+
+impl Default for Metadata {
+    /// Metadata with the current version of the crate and nothing else.
+    fn default() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            seed: None,
+            container: None,
+            args: vec![],
+        }
+    }
+}
+
+impl Metadata {
+    /// Write the `<metadata>` element with this metadata and all the given
+    /// settings, palette included.
+    pub fn write(&self, out: &mut impl Write, cfg: &Settings) -> io::Result<()> {
+        writeln!(out, "<metadata>")?;
+        write!(
+            out,
+            r#"<cp:packing xmlns:cp="{}" version="{}""#,
+            NAMESPACE,
+            escape(&self.version)
+        )?;
+        if let Some(seed) = self.seed {
+            write!(out, r#" seed="{}""#, seed)?;
+        }
+        writeln!(out, ">")?;
+
+        write!(
+            out,
+            r#"<cp:settings min-radius="{}" padding="{}" inside="{}" target-area="{}" max-stall-iterations="{}" palette="{}""#,
+            cfg.min_radius,
+            cfg.padding,
+            cfg.inside,
+            cfg.target_area,
+            cfg.max_stall_iterations,
+            escape(&cfg.palette.join(" ")),
+        )?;
+        if let Some(stop) = &cfg.stop {
+            write!(out, r#" stop="{}""#, escape(&stop.to_string()))?;
+        }
+        if cfg.placement != Placement::First {
+            write!(out, r#" placement="{}""#, cfg.placement)?;
+        }
+        if cfg.sampler != CandidateSampler::Uniform {
+            write!(out, r#" sampler="{}""#, cfg.sampler)?;
+        }
+        writeln!(out, " />")?;
+
+        if let Some(container) = &self.container {
+            writeln!(out, "<cp:container>{}</cp:container>", escape(container))?;
+        }
+
+        for arg in &self.args {
+            writeln!(out, "<cp:arg>{}</cp:arg>", escape(arg))?;
+        }
+
+        writeln!(out, "</cp:packing>")?;
+        writeln!(out, "</metadata>")
+    }
+}
+
+/// Read the metadata of the SVG file at the given path, see `parse`.
+pub fn load(path: impl AsRef<Path>) -> Result<Metadata, SvgError> {
+    let data = fs::read_to_string(path)?;
+    parse(&data)
+}
+
+/// Extract the metadata written by `dump_svg` from the given SVG document.
+pub fn parse(data: &str) -> Result<Metadata, SvgError> {
+    // `dump_svg` writes the SVG 1.1 doctype
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..roxmltree::ParsingOptions::default()
+    };
+    let doc = roxmltree::Document::parse_with_options(data, options)?;
+
+    let packing = doc
+        .descendants()
+        .find(|n| n.has_tag_name((NAMESPACE, "packing")))
+        .ok_or(SvgError::MissingMetadata)?;
+
+    let invalid = |attribute| SvgError::InvalidAttribute {
+        element: "cp:packing".to_string(),
+        attribute,
+    };
+
+    let version = packing
+        .attribute("version")
+        .ok_or_else(|| invalid("version"))?;
+    let seed = packing
+        .attribute("seed")
+        .map(|s| s.parse().map_err(|_| invalid("seed")))
+        .transpose()?;
+
+    let text = |name: &'static str| {
+        packing
+            .children()
+            .filter(move |n| n.has_tag_name((NAMESPACE, name)))
+            .map(|n| n.text().unwrap_or("").to_string())
+    };
+
+    Ok(Metadata {
+        version: version.to_string(),
+        seed,
+        container: text("container").next(),
+        args: text("arg").collect(),
+    })
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
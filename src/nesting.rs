@@ -0,0 +1,143 @@
+//! Turn a set of closed contours into containers with holes.
+//!
+//! Contours are arranged in a tree by containment: outer boundaries contain
+//! holes, which in turn can contain islands and so on. The fill rule decides
+//! which of the regions between a contour and its children are filled.
+
+use crate::{Polyline, Shape};
+
+/// How to decide which parts of a path with overlapping subpaths are filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Whether a region crossed `depth` times from the outside with the given
+    /// winding number is filled.
+    fn is_filled(self, depth: usize, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => depth % 2 == 1,
+        }
+    }
+}
+
+/// Build the containers described by the given closed contours.
+///
+/// Each filled region that is not surrounded by another filled region becomes
+/// a `Polyline` whose holes are the unfilled regions directly inside it.
+/// Islands inside holes become containers on their own. Contours with less
+/// than 3 points are ignored and the contours are assumed not to intersect
+/// each other, so glyphs with overlapping contours, which are common in
+/// variable fonts, are not supported.
+pub fn nest(contours: Vec<Vec<(f32, f32)>>, fill_rule: FillRule) -> Vec<Polyline> {
+    let mut contours = contours
+        .into_iter()
+        .filter(|c| c.len() >= 3)
+        .map(|c| {
+            let orientation = signed_area(&c).signum() as i32;
+            (orientation, Polyline::new(c).unwrap())
+        })
+        .collect::<Vec<_>>();
+
+    // parents are always bigger than their children
+    contours.sort_by(|(_, a), (_, b)| b.area().total_cmp(&a.area()));
+
+    // containers are `None` once holes cover them entirely
+    let mut containers: Vec<Option<Polyline>> = vec![];
+
+    // indices of the containers each filled region was split in
+    let mut pieces: Vec<Vec<usize>> = vec![];
+
+    // depth, winding number and index in `pieces` of the region that owns the
+    // region inside each contour, if it's filled
+    let mut regions: Vec<(usize, i32, Option<usize>)> = Vec::with_capacity(contours.len());
+
+    for (i, (orientation, contour)) in contours.iter().enumerate() {
+        // the smallest contour containing this one is its parent
+        let parent = (0..i).rev().find(|&p| contains(&contours[p].1, contour));
+        let (depth, winding, owner) = match parent {
+            Some(p) => regions[p],
+            None => (0, 0, None),
+        };
+
+        let depth = depth + 1;
+        let winding = winding + orientation;
+
+        let owner = match (owner, fill_rule.is_filled(depth, winding)) {
+            (Some(owner), true) => Some(owner),
+            (None, true) => {
+                containers.push(Some(contour.clone()));
+                pieces.push(vec![containers.len() - 1]);
+                Some(pieces.len() - 1)
+            }
+            (Some(owner), false) => {
+                cut_hole(&mut containers, &mut pieces[owner], contour);
+                None
+            }
+            (None, false) => None,
+        };
+
+        regions.push((depth, winding, owner));
+    }
+
+    containers.into_iter().flatten().collect()
+}
+
+/// Cut the given hole out of the given pieces of a region. Holes touching the
+/// boundary can split a piece, the new pieces are added to `containers`.
+fn cut_hole(containers: &mut Vec<Option<Polyline>>, pieces: &mut Vec<usize>, hole: &Polyline) {
+    for i in pieces.clone() {
+        let piece = match &mut containers[i] {
+            Some(piece) if piece.bbox().overlaps(&hole.bbox()) => piece,
+            _ => continue,
+        };
+
+        if piece.push_hole(hole.clone()) {
+            continue;
+        }
+
+        // either the hole doesn't overlap the piece, which is left as it is,
+        // or it splits it
+        let mut split = piece.difference(hole);
+        containers[i] = split.pop();
+        for p in split {
+            containers.push(Some(p));
+            pieces.push(containers.len() - 1);
+        }
+    }
+}
+
+/// Whether `inner` is inside `outer`, decided by the majority of its points so
+/// that contours touching each other are handled gracefully.
+fn contains(outer: &Polyline, inner: &Polyline) -> bool {
+    if !outer.bbox().contains(&inner.bbox()) {
+        return false;
+    }
+
+    let mut balance = 0;
+    for &(x, y) in inner.points() {
+        let d = outer.sdf(x, y);
+        if d < 0.0 {
+            balance += 1;
+        } else if d > 0.0 {
+            balance -= 1;
+        }
+    }
+
+    balance > 0
+}
+
+/// Area of the given polygon, positive if its points are in clockwise order
+/// in a y-down coordinate system.
+pub fn signed_area(points: &[(f32, f32)]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        let (x0, y0) = points[i];
+        let (x1, y1) = points[(i + 1) % points.len()];
+        area += x0 * y1 - x1 * y0;
+    }
+    area / 2.0
+}
@@ -0,0 +1,106 @@
+//! Color the packed shapes by sampling an image.
+
+use std::str::FromStr;
+
+use image::RgbImage;
+
+use crate::{Bbox, PackShape, Packable, Shape};
+
+/// Which pixels of the image decide the color of a shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sampling {
+    /// The pixel under the center of the shape.
+    Center,
+
+    /// The mean of the pixels whose center is inside the shape.
+    Mean,
+}
+
+impl FromStr for Sampling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "center" => Ok(Sampling::Center),
+            "mean" => Ok(Sampling::Mean),
+            _ => Err(format!("unknown sampling {}, use center or mean", s)),
+        }
+    }
+}
+
+/// Set the fill of all the shapes packed inside root, at any depth, to the
+/// color of the image at their location. The image is stretched to cover
+/// `area`, which is usually the bbox of root or of all the roots that are
+/// drawn together.
+pub fn paint<S: Shape, C: Packable>(
+    root: &mut PackShape<S, C>,
+    image: &RgbImage,
+    area: &Bbox,
+    sampling: Sampling,
+) {
+    let mut stack = root.children.iter_mut().collect::<Vec<_>>();
+
+    while let Some(shape) = stack.pop() {
+        let [r, g, b] = match sampling {
+            Sampling::Center => {
+                let (x, y) = shape.center();
+                center_color(image, area, x, y)
+            }
+            Sampling::Mean => mean_color(image, area, shape),
+        };
+        shape.fill = Some(format!("#{:02x}{:02x}{:02x}", r, g, b));
+
+        stack.extend(shape.children.iter_mut());
+    }
+}
+
+fn center_color(image: &RgbImage, area: &Bbox, x: f32, y: f32) -> [u8; 3] {
+    let (i, j) = to_pixel(image.dimensions(), area, x, y);
+    image.get_pixel(i, j).0
+}
+
+fn mean_color(image: &RgbImage, area: &Bbox, shape: &impl Shape) -> [u8; 3] {
+    let bbox = shape.bbox();
+    let (i0, j0) = to_pixel(image.dimensions(), area, bbox.x0(), bbox.y0());
+    let (i1, j1) = to_pixel(image.dimensions(), area, bbox.x1(), bbox.y1());
+
+    let pw = area.width() / image.width() as f32;
+    let ph = area.height() / image.height() as f32;
+
+    let mut sum = [0_u64; 3];
+    let mut n = 0;
+    for j in j0..=j1 {
+        for i in i0..=i1 {
+            let x = area.x0() + (i as f32 + 0.5) * pw;
+            let y = area.y0() + (j as f32 + 0.5) * ph;
+            if shape.sdf(x, y) > 0.0 {
+                continue;
+            }
+
+            for (s, c) in sum.iter_mut().zip(image.get_pixel(i, j).0) {
+                *s += u64::from(c);
+            }
+            n += 1;
+        }
+    }
+
+    // shapes smaller than a pixel might not contain any pixel center
+    if n == 0 {
+        let (x, y) = shape.center();
+        return center_color(image, area, x, y);
+    }
+
+    sum.map(|s| (s / n) as u8)
+}
+
+/// Pixel under the given point of an image of the given size stretched to
+/// cover `area`, clamped to the image.
+pub(crate) fn to_pixel((width, height): (u32, u32), area: &Bbox, x: f32, y: f32) -> (u32, u32) {
+    let u = (x - area.x0()) / area.width() * width as f32;
+    let v = (y - area.y0()) / area.height() * height as f32;
+
+    (
+        (u.max(0.0) as u32).min(width - 1),
+        (v.max(0.0) as u32).min(height - 1),
+    )
+}
@@ -0,0 +1,479 @@
+//! Parser for the `d` attribute of SVG paths.
+//!
+//! All the commands are supported, curves and arcs are flattened into line
+//! segments that are at most `tolerance` away from the real curve, which must
+//! be positive.
+
+use std::{error::Error, f32::consts::PI, fmt};
+
+use crate::Polyline;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// A character that is neither a command nor part of a number.
+    UnexpectedChar { pos: usize, ch: char },
+
+    /// A command is missing some of its arguments.
+    MissingArgument { pos: usize, cmd: char },
+
+    /// The path doesn't start with a moveto.
+    MissingMoveTo { pos: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedChar { pos, ch } => {
+                write!(f, "unexpected character '{}' at {}", ch, pos)
+            }
+            ParseError::MissingArgument { pos, cmd } => {
+                write!(f, "missing argument for command '{}' at {}", cmd, pos)
+            }
+            ParseError::MissingMoveTo { pos } => {
+                write!(f, "path must start with a moveto, found command at {}", pos)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parse the given path data into one `Polyline` per subpath, subpaths with
+/// less than 3 points are dropped. No hole detection is performed, use
+/// `parse_subpaths` and `nesting::nest` for that.
+pub fn parse(d: &str, tolerance: f32) -> Result<Vec<Polyline>, ParseError> {
+    Ok(parse_subpaths(d, tolerance)?
+        .into_iter()
+        .filter(|path| path.len() >= 3)
+        .filter_map(Polyline::new)
+        .collect())
+}
+
+/// Parse the given path data into the points of its subpaths. Subpaths are
+/// always considered closed and the closing point is not repeated.
+pub fn parse_subpaths(d: &str, tolerance: f32) -> Result<Vec<Vec<(f32, f32)>>, ParseError> {
+    let mut parser = Parser {
+        lexer: Lexer { data: d, pos: 0 },
+        tolerance,
+        paths: vec![],
+        path: vec![],
+        cur: (0.0, 0.0),
+        start: (0.0, 0.0),
+        last_ctrl: None,
+    };
+
+    parser.parse()?;
+    Ok(parser.paths)
+}
+
+struct Lexer<'a> {
+    data: &'a str,
+    pos: usize,
+}
+
+struct Parser<'a> {
+    lexer: Lexer<'a>,
+    tolerance: f32,
+
+    paths: Vec<Vec<(f32, f32)>>,
+    path: Vec<(f32, f32)>,
+
+    cur: (f32, f32),
+    start: (f32, f32),
+
+    /// Last control point of the previous command alongside whether it was a
+    /// cubic or not, used to reflect the control point of smooth curves.
+    last_ctrl: Option<((f32, f32), bool)>,
+}
+
+impl<'a> Lexer<'a> {
+    fn skip_separators(&mut self) {
+        let rest = &self.data[self.pos..];
+        let trimmed = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        self.pos += rest.len() - trimmed.len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_separators();
+        self.data[self.pos..].chars().next()
+    }
+
+    fn command(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            self.pos += 1;
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn has_number(&mut self) -> bool {
+        matches!(self.peek(), Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        if !self.has_number() {
+            return None;
+        }
+
+        let bytes = self.data.as_bytes();
+        let start = self.pos;
+        let mut end = start;
+
+        if bytes[end] == b'-' || bytes[end] == b'+' {
+            end += 1;
+        }
+
+        let mut seen_dot = false;
+        while end < bytes.len()
+            && (bytes[end].is_ascii_digit() || (bytes[end] == b'.' && !seen_dot))
+        {
+            seen_dot |= bytes[end] == b'.';
+            end += 1;
+        }
+
+        if end < bytes.len() && (bytes[end] == b'e' || bytes[end] == b'E') {
+            let mut exp_end = end + 1;
+            if exp_end < bytes.len() && (bytes[exp_end] == b'-' || bytes[exp_end] == b'+') {
+                exp_end += 1;
+            }
+            if exp_end < bytes.len() && bytes[exp_end].is_ascii_digit() {
+                while exp_end < bytes.len() && bytes[exp_end].is_ascii_digit() {
+                    exp_end += 1;
+                }
+                end = exp_end;
+            }
+        }
+
+        let n = self.data[start..end].parse().ok()?;
+        self.pos = end;
+        Some(n)
+    }
+
+    /// Arc flags are single digits that don't need to be separated from what
+    /// follows them.
+    fn flag(&mut self) -> Option<bool> {
+        match self.peek()? {
+            '0' => {
+                self.pos += 1;
+                Some(false)
+            }
+            '1' => {
+                self.pos += 1;
+                Some(true)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn parse(&mut self) -> Result<(), ParseError> {
+        let mut first = true;
+
+        while let Some(c) = self.lexer.peek() {
+            let pos = self.lexer.pos;
+            let cmd = self
+                .lexer
+                .command()
+                .ok_or(ParseError::UnexpectedChar { pos, ch: c })?;
+
+            if first && cmd != 'M' && cmd != 'm' {
+                return Err(ParseError::MissingMoveTo { pos });
+            }
+            first = false;
+
+            self.command(cmd, pos)?;
+        }
+
+        self.finish_subpath();
+        Ok(())
+    }
+
+    fn command(&mut self, cmd: char, pos: usize) -> Result<(), ParseError> {
+        let relative = cmd.is_ascii_lowercase();
+
+        // commands other than closepath can be repeated by just listing more
+        // arguments, the first set is mandatory though.
+        let mut repeated = false;
+        loop {
+            if repeated && !self.lexer.has_number() {
+                break;
+            }
+
+            let origin = if relative { self.cur } else { (0.0, 0.0) };
+
+            match cmd.to_ascii_uppercase() {
+                'M' => {
+                    let p = self.point(origin, cmd, pos)?;
+                    if repeated {
+                        self.line_to(p);
+                    } else {
+                        self.finish_subpath();
+                        self.start = p;
+                        self.cur = p;
+                        self.path.push(p);
+                    }
+                }
+                'L' => {
+                    let p = self.point(origin, cmd, pos)?;
+                    self.line_to(p);
+                }
+                'H' => {
+                    let x = self.number(cmd, pos)?;
+                    self.line_to((origin.0 + x, self.cur.1));
+                }
+                'V' => {
+                    let y = self.number(cmd, pos)?;
+                    self.line_to((self.cur.0, origin.1 + y));
+                }
+                'C' => {
+                    let c1 = self.point(origin, cmd, pos)?;
+                    let c2 = self.point(origin, cmd, pos)?;
+                    let p = self.point(origin, cmd, pos)?;
+                    self.cubic_to(c1, c2, p);
+                }
+                'S' => {
+                    let c1 = self.reflected_ctrl(true);
+                    let c2 = self.point(origin, cmd, pos)?;
+                    let p = self.point(origin, cmd, pos)?;
+                    self.cubic_to(c1, c2, p);
+                }
+                'Q' => {
+                    let c = self.point(origin, cmd, pos)?;
+                    let p = self.point(origin, cmd, pos)?;
+                    self.quad_to(c, p);
+                }
+                'T' => {
+                    let c = self.reflected_ctrl(false);
+                    let p = self.point(origin, cmd, pos)?;
+                    self.quad_to(c, p);
+                }
+                'A' => {
+                    let rx = self.number(cmd, pos)?;
+                    let ry = self.number(cmd, pos)?;
+                    let angle = self.number(cmd, pos)?;
+                    let large_arc = self.flag(cmd, pos)?;
+                    let sweep = self.flag(cmd, pos)?;
+                    let p = self.point(origin, cmd, pos)?;
+                    self.arc_to(rx, ry, angle.to_radians(), large_arc, sweep, p);
+                }
+                'Z' => {
+                    self.finish_subpath();
+                    self.cur = self.start;
+                    self.last_ctrl = None;
+                    break;
+                }
+                _ => {
+                    return Err(ParseError::UnexpectedChar { pos, ch: cmd });
+                }
+            }
+
+            repeated = true;
+        }
+
+        Ok(())
+    }
+
+    fn number(&mut self, cmd: char, pos: usize) -> Result<f32, ParseError> {
+        self.lexer
+            .number()
+            .ok_or(ParseError::MissingArgument { pos, cmd })
+    }
+
+    fn flag(&mut self, cmd: char, pos: usize) -> Result<bool, ParseError> {
+        self.lexer
+            .flag()
+            .ok_or(ParseError::MissingArgument { pos, cmd })
+    }
+
+    fn point(
+        &mut self,
+        (ox, oy): (f32, f32),
+        cmd: char,
+        pos: usize,
+    ) -> Result<(f32, f32), ParseError> {
+        let x = self.number(cmd, pos)?;
+        let y = self.number(cmd, pos)?;
+        Ok((ox + x, oy + y))
+    }
+
+    fn finish_subpath(&mut self) {
+        if self.path.len() > 1 && self.path.first() == self.path.last() {
+            self.path.pop();
+        }
+
+        if !self.path.is_empty() {
+            self.paths.push(std::mem::take(&mut self.path));
+        }
+    }
+
+    fn ensure_subpath(&mut self) {
+        // drawing after a closepath starts a new subpath from the start of the
+        // previous one
+        if self.path.is_empty() {
+            self.path.push(self.cur);
+        }
+    }
+
+    fn line_to(&mut self, p: (f32, f32)) {
+        self.ensure_subpath();
+        self.path.push(p);
+        self.cur = p;
+        self.last_ctrl = None;
+    }
+
+    fn reflected_ctrl(&self, cubic: bool) -> (f32, f32) {
+        match self.last_ctrl {
+            Some(((x, y), was_cubic)) if was_cubic == cubic => {
+                (2.0 * self.cur.0 - x, 2.0 * self.cur.1 - y)
+            }
+            _ => self.cur,
+        }
+    }
+
+    fn cubic_to(&mut self, c1: (f32, f32), c2: (f32, f32), p: (f32, f32)) {
+        self.ensure_subpath();
+
+        let p0 = self.cur;
+
+        // Wang's formula for the number of segments needed to stay within
+        // tolerance of the curve.
+        let dd = |a: (f32, f32), b: (f32, f32), c: (f32, f32)| {
+            (a.0 - 2.0 * b.0 + c.0).hypot(a.1 - 2.0 * b.1 + c.1)
+        };
+        let m = dd(p0, c1, c2).max(dd(c1, c2, p));
+        let n = segments((0.75 * m / self.tolerance).sqrt());
+
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            self.path.push((
+                a * p0.0 + b * c1.0 + c * c2.0 + d * p.0,
+                a * p0.1 + b * c1.1 + c * c2.1 + d * p.1,
+            ));
+        }
+
+        self.cur = p;
+        self.last_ctrl = Some((c2, true));
+    }
+
+    fn quad_to(&mut self, c: (f32, f32), p: (f32, f32)) {
+        self.ensure_subpath();
+
+        let p0 = self.cur;
+
+        let m = (p0.0 - 2.0 * c.0 + p.0).hypot(p0.1 - 2.0 * c.1 + p.1);
+        let n = segments((0.25 * m / self.tolerance).sqrt());
+
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            let u = 1.0 - t;
+            let (a, b, d) = (u * u, 2.0 * u * t, t * t);
+            self.path
+                .push((a * p0.0 + b * c.0 + d * p.0, a * p0.1 + b * c.1 + d * p.1));
+        }
+
+        self.cur = p;
+        self.last_ctrl = Some((c, false));
+    }
+
+    /// Flatten an elliptical arc, see the implementation notes of the SVG
+    /// specification for the conversion to center parametrization.
+    fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        angle: f32,
+        large_arc: bool,
+        sweep: bool,
+        p: (f32, f32),
+    ) {
+        let p0 = self.cur;
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+
+        if p0 == p {
+            return;
+        }
+        if rx == 0.0 || ry == 0.0 {
+            self.line_to(p);
+            return;
+        }
+
+        self.ensure_subpath();
+
+        let (sin, cos) = angle.sin_cos();
+        let dx = (p0.0 - p.0) / 2.0;
+        let dy = (p0.1 - p.1) / 2.0;
+        let x1 = cos * dx + sin * dy;
+        let y1 = -sin * dx + cos * dy;
+
+        // scale up radii that are too small to connect the endpoints
+        let l = (x1 / rx).powi(2) + (y1 / ry).powi(2);
+        if l > 1.0 {
+            rx *= l.sqrt();
+            ry *= l.sqrt();
+        }
+
+        let num = (rx * ry).powi(2) - (rx * y1).powi(2) - (ry * x1).powi(2);
+        let den = (rx * y1).powi(2) + (ry * x1).powi(2);
+        let mut k = (num / den).max(0.0).sqrt();
+        if large_arc == sweep {
+            k = -k;
+        }
+
+        let cx1 = k * rx * y1 / ry;
+        let cy1 = -k * ry * x1 / rx;
+        let cx = cos * cx1 - sin * cy1 + (p0.0 + p.0) / 2.0;
+        let cy = sin * cx1 + cos * cy1 + (p0.1 + p.1) / 2.0;
+
+        let vector_angle = |ux: f32, uy: f32, vx: f32, vy: f32| {
+            let a = (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+            if a.is_nan() {
+                0.0
+            } else {
+                a
+            }
+        };
+
+        let (ux, uy) = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+        let (vx, vy) = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+        let theta = vector_angle(1.0, 0.0, ux, uy);
+        let mut delta = vector_angle(ux, uy, vx, vy);
+        if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        } else if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        }
+
+        // the sagitta of each segment must be within tolerance
+        let r = rx.max(ry);
+        let step = 2.0 * (1.0 - (self.tolerance / r).min(1.0)).acos();
+        let n = segments(delta.abs() / step);
+
+        for i in 1..n {
+            let a = theta + delta * i as f32 / n as f32;
+            let (ex, ey) = (rx * a.cos(), ry * a.sin());
+            self.path
+                .push((cos * ex - sin * ey + cx, sin * ex + cos * ey + cy));
+        }
+        self.path.push(p);
+
+        self.cur = p;
+        self.last_ctrl = None;
+    }
+}
+
+/// Number of segments to use to flatten a curve, capped to avoid blowing up
+/// with tiny tolerances.
+fn segments(n: f32) -> usize {
+    const MAX_SEGMENTS: f32 = 4096.0;
+
+    if n.is_nan() {
+        1
+    } else {
+        n.ceil().clamp(1.0, MAX_SEGMENTS) as usize
+    }
+}
@@ -0,0 +1,233 @@
+//! Vector PDF output of packings on a physical page.
+
+use std::{
+    f32::consts::FRAC_PI_2,
+    io::{self, Write},
+    str::FromStr,
+};
+
+use crate::{bounds, contour, no_roots, raster, PackShape, Packable, Settings, Shape};
+
+/// Number of samples per side used to trace the contour of shapes without
+/// an exact outline.
+const CONTOUR_SAMPLES: f32 = 256.0;
+
+/// Points per millimeter.
+const PT_PER_MM: f32 = 72.0 / 25.4;
+
+/// Size of a page and of its margins, in millimeters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub width: f32,
+    pub height: f32,
+    pub margin: f32,
+}
+
+impl Page {
+    pub const A4: Page = Page {
+        width: 210.0,
+        height: 297.0,
+        margin: 10.0,
+    };
+
+    pub const A3: Page = Page {
+        width: 297.0,
+        height: 420.0,
+        margin: 10.0,
+    };
+
+    pub const LETTER: Page = Page {
+        width: 215.9,
+        height: 279.4,
+        margin: 10.0,
+    };
+
+    /// The same page rotated so that it's wider than tall.
+    pub fn landscape(&self) -> Page {
+        Page {
+            width: self.width.max(self.height),
+            height: self.width.min(self.height),
+            margin: self.margin,
+        }
+    }
+}
+
+impl FromStr for Page {
+    type Err = String;
+
+    /// Parse either the name of a known page size or `<width>x<height>` in
+    /// millimeters.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "a4" => Ok(Page::A4),
+            "a3" => Ok(Page::A3),
+            "letter" => Ok(Page::LETTER),
+            size => {
+                let (w, h) = size
+                    .split_once('x')
+                    .ok_or_else(|| format!("unknown page size {}", s))?;
+                let w = w.parse().map_err(|_| format!("invalid page width {}", w))?;
+                let h = h
+                    .parse()
+                    .map_err(|_| format!("invalid page height {}", h))?;
+
+                Ok(Page {
+                    width: w,
+                    height: h,
+                    ..Page::A4
+                })
+            }
+        }
+    }
+}
+
+/// Write a single page PDF with the same layout, colors and draw order of
+/// `dump_svg`. The drawing is scaled to fit inside the margins of the page
+/// and centered.
+pub fn dump_pdf<S: Shape, C: Packable>(
+    out: &mut impl Write,
+    roots: &[PackShape<S, C>],
+    cfg: &Settings,
+    page: &Page,
+) -> io::Result<()> {
+    let bbox = bounds(roots).ok_or_else(no_roots)?;
+
+    let (width, height) = (page.width * PT_PER_MM, page.height * PT_PER_MM);
+    let margin = page.margin * PT_PER_MM;
+    let (aw, ah) = (width - 2.0 * margin, height - 2.0 * margin);
+
+    // flip the y axis so that shapes can be written in their own coordinates
+    let s = (aw / bbox.width()).min(ah / bbox.height());
+    let tx = margin + (aw - s * bbox.width()) / 2.0 - s * bbox.x0();
+    let ty = height - margin - (ah - s * bbox.height()) / 2.0 + s * bbox.y0();
+
+    let mut content = vec![];
+    writeln!(content, "q {} 0 0 {} {} {} cm", s, -s, tx, ty)?;
+
+    write_fill(&mut content, &cfg.palette[0])?;
+    bbox.write_pdf(&mut content)?;
+    writeln!(content, "f*")?;
+
+    let mut stack = vec![];
+
+    for root in roots {
+        write_fill(&mut content, root.fill_color(cfg))?;
+        root.write_pdf(&mut content)?;
+        writeln!(content, "f*")?;
+        stack.extend(root.children());
+    }
+
+    while let Some(c) = stack.pop() {
+        write_fill(&mut content, c.fill_color(cfg))?;
+        c.write_pdf(&mut content)?;
+        writeln!(content, "f*")?;
+        stack.extend(c.children());
+    }
+
+    writeln!(content, "Q")?;
+
+    let mut doc = b"%PDF-1.4\n".to_vec();
+    let mut offsets = vec![];
+    let mut object = |doc: &mut Vec<u8>, body: &[u8]| -> io::Result<()> {
+        offsets.push(doc.len());
+        writeln!(doc, "{} 0 obj", offsets.len())?;
+        doc.extend_from_slice(body);
+        writeln!(doc, "\nendobj")
+    };
+
+    object(&mut doc, b"<< /Type /Catalog /Pages 2 0 R >>")?;
+    object(&mut doc, b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>")?;
+    object(
+        &mut doc,
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents 4 0 R /Resources << >> >>",
+            width, height
+        )
+        .as_bytes(),
+    )?;
+
+    let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+    stream.extend_from_slice(&content);
+    stream.extend_from_slice(b"endstream");
+    object(&mut doc, &stream)?;
+
+    let xref = doc.len();
+    writeln!(doc, "xref\n0 {}", offsets.len() + 1)?;
+    writeln!(doc, "0000000000 65535 f ")?;
+    for offset in &offsets {
+        writeln!(doc, "{:010} 00000 n ", offset)?;
+    }
+    writeln!(
+        doc,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF",
+        offsets.len() + 1,
+        xref
+    )?;
+
+    out.write_all(&doc)
+}
+
+fn write_fill(w: &mut impl Write, color: &str) -> io::Result<()> {
+    let [r, g, b] = raster::parse_color(color).map(|c| f32::from(c) / 255.0);
+    writeln!(w, "{} {} {} rg", r, g, b)
+}
+
+/// Write the contour of the zero level set of the sdf of the given shape,
+/// used by shapes without an exact outline.
+pub fn write_contour(w: &mut impl Write, shape: &impl Shape) -> io::Result<()> {
+    let bbox = shape.bbox();
+    let step = bbox.width().max(bbox.height()) / CONTOUR_SAMPLES;
+
+    for c in contour::contours(shape, step) {
+        write_polygon(w, &c)?;
+    }
+    Ok(())
+}
+
+/// Write a closed polygon with the given vertices.
+pub fn write_polygon(w: &mut impl Write, points: &[(f32, f32)]) -> io::Result<()> {
+    for (i, (x, y)) in points.iter().enumerate() {
+        writeln!(w, "{} {} {}", x, y, if i == 0 { "m" } else { "l" })?;
+    }
+    writeln!(w, "h")
+}
+
+/// Write a closed ellipse.
+pub fn write_ellipse(w: &mut impl Write, x: f32, y: f32, rx: f32, ry: f32) -> io::Result<()> {
+    writeln!(w, "{} {} m", x + rx, y)?;
+    write_arc(w, (x, y), (rx, ry), 0.0, 4.0 * FRAC_PI_2)?;
+    writeln!(w, "h")
+}
+
+/// Continue the current path with an elliptical arc going from angle `a0` to
+/// angle `a1`, the current point must be the start of the arc.
+pub fn write_arc(
+    w: &mut impl Write,
+    (cx, cy): (f32, f32),
+    (rx, ry): (f32, f32),
+    a0: f32,
+    a1: f32,
+) -> io::Result<()> {
+    // cubic approximations of arcs up to a quarter turn are accurate enough
+    let n = ((a1 - a0).abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+    let da = (a1 - a0) / n as f32;
+    let k = 4.0 / 3.0 * (da / 4.0).tan();
+
+    for i in 0..n {
+        let (s0, c0) = (a0 + da * i as f32).sin_cos();
+        let (s1, c1) = (a0 + da * (i + 1) as f32).sin_cos();
+
+        writeln!(
+            w,
+            "{} {} {} {} {} {} c",
+            cx + rx * (c0 - k * s0),
+            cy + ry * (s0 + k * c0),
+            cx + rx * (c1 + k * s1),
+            cy + ry * (s1 - k * c1),
+            cx + rx * c1,
+            cy + ry * s1
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Strategies choosing where the next shape is placed, see
+//! `Settings::placement`.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Placement {
+    /// Place a shape at the first random point where it fits.
+    #[default]
+    First,
+
+    /// Draw `k` random points at each attempt and place a shape at the one
+    /// where it can be the biggest, which places the big shapes first and
+    /// packs them more densely.
+    ///
+    /// If `anneal_to` is given the number of points changes linearly with
+    /// the coverage of the root, from `k` when it's empty to `anneal_to` when
+    /// it's full.
+    BestOf { k: usize, anneal_to: Option<usize> },
+
+    /// Place each shape in the largest empty circle left, within a tenth of
+    /// the minimum radius, until there's none as big as the minimum radius.
+    /// It doesn't use random points, and the shapes are placed in decreasing
+    /// radius order.
+    LargestEmpty,
+}
+
+impl Placement {
+    /// Number of points to draw at an attempt, given the fraction of the root
+    /// covered so far.
+    pub fn candidates(&self, coverage: f32) -> usize {
+        match *self {
+            Placement::First | Placement::LargestEmpty => 1,
+            Placement::BestOf { k, anneal_to: None } => k.max(1),
+            Placement::BestOf {
+                k,
+                anneal_to: Some(to),
+            } => {
+                let t = coverage.clamp(0.0, 1.0);
+                let k = k as f32 + (to as f32 - k as f32) * t;
+                (k.round() as usize).max(1)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Placement::First => write!(f, "first"),
+            Placement::BestOf { k, anneal_to: None } => write!(f, "best-of:{}", k),
+            Placement::BestOf {
+                k,
+                anneal_to: Some(to),
+            } => write!(f, "best-of:{}:{}", k, to),
+            Placement::LargestEmpty => write!(f, "largest-empty"),
+        }
+    }
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    /// Parse `first`, `best-of:<k>[:<k when full>]` or `largest-empty`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let kind = parts.next().unwrap_or("");
+        let mut count = |name: &str| {
+            parts
+                .next()
+                .map(|n| {
+                    n.parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("invalid {} {}", name, n))
+                })
+                .transpose()
+        };
+
+        let placement = match kind {
+            "first" => Placement::First,
+            "largest-empty" => Placement::LargestEmpty,
+            "best-of" => Placement::BestOf {
+                k: count("k")?.ok_or_else(|| format!("missing k in {}", s))?,
+                anneal_to: count("k")?,
+            },
+            _ => {
+                return Err(format!(
+                    "unknown placement {}, use first, best-of:<k>[:<k when full>] or largest-empty",
+                    s
+                ))
+            }
+        };
+
+        match parts.next() {
+            Some(extra) => Err(format!("unexpected {} in {}", extra, s)),
+            None => Ok(placement),
+        }
+    }
+}
@@ -0,0 +1,564 @@
+//! Pen plotter output of packings as HPGL or G-code.
+//!
+//! Shapes are drawn as strokes, optionally filled with hatching or concentric
+//! rings, and grouped in layers, usually one per color so that each color can
+//! be drawn with its own pen. The strokes of each layer are reordered to keep
+//! the travel of the pen short, see `travel`.
+
+use std::{
+    io::{self, Write},
+    str::FromStr,
+};
+
+use crate::{bounds, contour, no_roots, travel, Bbox, PackShape, Packable, Settings, Shape};
+
+/// Number of samples per side used to trace the contour of shapes without
+/// an exact outline.
+const CONTOUR_SAMPLES: f32 = 256.0;
+
+/// HPGL plotter units per millimeter.
+const HPGL_UNITS_PER_MM: f32 = 40.0;
+
+/// Maximum number of hatch lines or concentric rings filling a shape.
+const MAX_FILL_LINES: usize = 1 << 16;
+
+/// A single pen down movement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stroke {
+    /// A whole circle, drawn as an arc if the output supports it.
+    Circle { x: f32, y: f32, radius: f32 },
+
+    /// An open polyline, closed ones repeat the first point at the end.
+    Line(Vec<(f32, f32)>),
+}
+
+/// How strokes are split in layers, each layer is drawn entirely before moving
+/// to the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    /// A layer for each color, so that each color can have its own pen.
+    Color,
+
+    /// A layer for each nesting depth, roots first.
+    Depth,
+
+    /// A single layer with all the strokes.
+    None,
+}
+
+/// How the packed shapes are filled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fill {
+    None,
+
+    /// Parallel lines `spacing` apart at the given angle in radians.
+    Hatch {
+        spacing: f32,
+        angle: f32,
+    },
+
+    /// Smaller copies of the shape every `spacing` towards its center.
+    Concentric {
+        spacing: f32,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct PlotSettings {
+    /// Millimeters per unit of the packing.
+    pub scale: f32,
+
+    /// Max distance between curves and the polylines approximating them, in
+    /// units of the packing.
+    pub tolerance: f32,
+
+    pub fill: Fill,
+
+    pub grouping: Grouping,
+
+    /// Reorder the strokes of each layer to shorten the travel of the pen,
+    /// otherwise they're drawn in the same order of `dump_svg`.
+    pub optimize_travel: bool,
+
+    /// G-code commands to lift and lower the pen and to pause for a pen
+    /// change between colors.
+    pub pen_up: String,
+    pub pen_down: String,
+    pub pen_change: String,
+
+    /// Speed of the pen while drawing and while moving with the pen up, in
+    /// millimeters per minute.
+    pub feed_rate: f32,
+    pub travel_rate: f32,
+}
+
+/// Strokes drawn together, with the same pen.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    /// The color of the strokes, or a description of the group they belong to
+    /// if they aren't grouped by color.
+    pub name: String,
+    pub strokes: Vec<Stroke>,
+}
+
+impl Default for PlotSettings {
+    fn default() -> Self {
+        Self {
+            scale: 0.1,
+            tolerance: 0.5,
+            fill: Fill::None,
+            grouping: Grouping::Color,
+            optimize_travel: true,
+            pen_up: "G0 Z5".to_string(),
+            pen_down: "G1 Z0".to_string(),
+            pen_change: "M0".to_string(),
+            feed_rate: 1500.0,
+            travel_rate: 3000.0,
+        }
+    }
+}
+
+impl Stroke {
+    /// Where the pen is lowered.
+    pub fn start(&self) -> (f32, f32) {
+        match self {
+            Stroke::Circle { x, y, radius } => (x + radius, *y),
+            Stroke::Line(points) => points[0],
+        }
+    }
+
+    /// Where the pen is lifted.
+    pub fn end(&self) -> (f32, f32) {
+        match self {
+            Stroke::Circle { .. } => self.start(),
+            Stroke::Line(points) => points[points.len() - 1],
+        }
+    }
+
+    /// Draw the stroke in the opposite direction.
+    pub fn reverse(&mut self) {
+        if let Stroke::Line(points) = self {
+            points.reverse();
+        }
+    }
+
+    /// Move the start of a closed line to the vertex that's closest to the
+    /// given points on the way in and out, other strokes are left as they are.
+    pub fn rotate_towards(&mut self, from: (f32, f32), to: Option<(f32, f32)>) {
+        let points = match self {
+            Stroke::Line(points) if points.len() > 2 && points[0] == points[points.len() - 1] => {
+                points
+            }
+            _ => return,
+        };
+
+        let cost = |&(x, y): &(f32, f32)| {
+            let d = (x - from.0).hypot(y - from.1);
+            d + to.map_or(0.0, |(tx, ty)| (x - tx).hypot(y - ty))
+        };
+
+        let n = points.len() - 1;
+        let best = (0..n)
+            .min_by(|&i, &j| cost(&points[i]).total_cmp(&cost(&points[j])))
+            .unwrap_or(0);
+
+        points.pop();
+        points.rotate_left(best);
+        points.push(points[0]);
+    }
+}
+
+impl FromStr for Grouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "color" => Ok(Grouping::Color),
+            "depth" => Ok(Grouping::Depth),
+            "none" => Ok(Grouping::None),
+            _ => Err(format!("unknown grouping {}, use color, depth or none", s)),
+        }
+    }
+}
+
+impl FromStr for Fill {
+    type Err = String;
+
+    /// Parse `none`, `hatch:<spacing>[:<angle in degrees>]` or
+    /// `concentric:<spacing>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let kind = parts.next().unwrap_or("");
+        let mut number =
+            |name: &str, default: Option<f32>, valid: fn(f32) -> bool| match parts.next() {
+                Some(n) => n
+                    .parse()
+                    .ok()
+                    .filter(|&n| valid(n))
+                    .ok_or_else(|| format!("invalid {} {}", name, n)),
+                None => default.ok_or_else(|| format!("missing {} in {}", name, s)),
+            };
+        let spacing = |n: f32| n.is_finite() && n > 0.0;
+
+        match kind {
+            "none" => Ok(Fill::None),
+            "hatch" => Ok(Fill::Hatch {
+                spacing: number("spacing", None, spacing)?,
+                angle: number("angle", Some(45.0), f32::is_finite)?.to_radians(),
+            }),
+            "concentric" => Ok(Fill::Concentric {
+                spacing: number("spacing", None, spacing)?,
+            }),
+            _ => Err(format!(
+                "unknown fill {}, use none, hatch:<spacing>[:<angle>] or concentric:<spacing>",
+                s
+            )),
+        }
+    }
+}
+
+/// Strokes of the given roots grouped as described by `plot.grouping`, in the
+/// order the groups are first found while walking the roots like `dump_svg`
+/// does. Colors are named after their value, depths as `depth <n>`.
+///
+/// Roots are only outlined, packed shapes are also filled as described by
+/// `plot.fill` without drawing over the shapes packed inside them.
+pub fn layers<S: Shape, C: Packable>(
+    roots: &[PackShape<S, C>],
+    cfg: &Settings,
+    plot: &PlotSettings,
+) -> Vec<Layer> {
+    let mut layers: Vec<Layer> = vec![];
+    let mut add = |color: &str, depth: usize, strokes: Vec<Stroke>| {
+        let name = match plot.grouping {
+            Grouping::Color => color.to_string(),
+            Grouping::Depth => format!("depth {}", depth),
+            Grouping::None => "all".to_string(),
+        };
+
+        match layers.iter_mut().find(|l| l.name == name) {
+            Some(layer) => layer.strokes.extend(strokes),
+            None => layers.push(Layer { name, strokes }),
+        };
+    };
+
+    let mut stack = vec![];
+
+    for root in roots {
+        add(root.fill_color(cfg), 0, root.strokes(plot.tolerance));
+        stack.extend(root.children().iter().map(|c| (c, 1)));
+    }
+
+    while let Some((c, depth)) = stack.pop() {
+        let mut strokes = c.strokes(plot.tolerance);
+        strokes.extend(fill_strokes(c, plot));
+        add(c.fill_color(cfg), depth, strokes);
+        stack.extend(c.children().iter().map(|c| (c, depth + 1)));
+    }
+
+    if plot.optimize_travel {
+        // the pen starts at the origin of the plotter, see `transform`
+        let bbox = bounds(roots).unwrap_or_else(|| Bbox::new(0.0, 0.0));
+        let mut pos = (bbox.x0(), bbox.y1());
+        for layer in &mut layers {
+            pos = travel::optimize(&mut layer.strokes, pos);
+        }
+    }
+
+    layers
+}
+
+/// Write the given roots as HPGL, selecting a new pen for each layer.
+pub fn dump_hpgl<S: Shape, C: Packable>(
+    out: &mut impl Write,
+    roots: &[PackShape<S, C>],
+    cfg: &Settings,
+    plot: &PlotSettings,
+) -> io::Result<()> {
+    let bbox = bounds(roots).ok_or_else(no_roots)?;
+    let to_plotter = transform(&bbox, plot.scale * HPGL_UNITS_PER_MM);
+    let point = |(x, y): (f32, f32)| {
+        let (x, y) = to_plotter(x, y);
+        format!("{},{}", x.round(), y.round())
+    };
+
+    writeln!(out, "IN;")?;
+    // VS is in centimeters per second
+    writeln!(out, "VS{};", (plot.feed_rate / 600.0).max(1.0).round())?;
+
+    for m in pen_moves(&bbox, layers(roots, cfg, plot)) {
+        match m {
+            PenMove::Layer { index, .. } => writeln!(out, "SP{};", index + 1)?,
+            PenMove::Up => writeln!(out, "PU;")?,
+            PenMove::Down => writeln!(out, "PD;")?,
+            PenMove::Travel(p) => writeln!(out, "PA{};", point(p))?,
+            PenMove::Draw(Stroke::Circle { x, y, .. }) => {
+                writeln!(out, "AA{},360;", point((x, y)))?
+            }
+            PenMove::Draw(Stroke::Line(points)) => {
+                let rest = points[1..].iter().map(|&p| point(p)).collect::<Vec<_>>();
+                writeln!(out, "PA{};", rest.join(","))?;
+            }
+        }
+    }
+
+    writeln!(out, "SP0;")
+}
+
+/// Write the given roots as G-code in millimeters, pausing with `pen_change`
+/// before each layer.
+pub fn dump_gcode<S: Shape, C: Packable>(
+    out: &mut impl Write,
+    roots: &[PackShape<S, C>],
+    cfg: &Settings,
+    plot: &PlotSettings,
+) -> io::Result<()> {
+    let bbox = bounds(roots).ok_or_else(no_roots)?;
+    let to_plotter = transform(&bbox, plot.scale);
+
+    writeln!(out, "G21")?;
+    writeln!(out, "G90")?;
+
+    for m in pen_moves(&bbox, layers(roots, cfg, plot)) {
+        match m {
+            PenMove::Layer { name, .. } => {
+                writeln!(out, "; {}", name)?;
+                writeln!(out, "{}", plot.pen_change)?;
+            }
+            PenMove::Up => writeln!(out, "{}", plot.pen_up)?,
+            PenMove::Down => writeln!(out, "{}", plot.pen_down)?,
+            PenMove::Travel((x, y)) => {
+                let (x, y) = to_plotter(x, y);
+                writeln!(out, "G0 X{:.3} Y{:.3} F{}", x, y, plot.travel_rate)?;
+            }
+            PenMove::Draw(Stroke::Circle { x, y, radius }) => {
+                // a whole turn ends where it starts, around the center on its left
+                let (px, py) = to_plotter(x + radius, y);
+                writeln!(
+                    out,
+                    "G2 X{:.3} Y{:.3} I{:.3} J0 F{}",
+                    px,
+                    py,
+                    -radius * plot.scale,
+                    plot.feed_rate
+                )?;
+            }
+            PenMove::Draw(Stroke::Line(points)) => {
+                for &(x, y) in &points[1..] {
+                    let (x, y) = to_plotter(x, y);
+                    writeln!(out, "G1 X{:.3} Y{:.3} F{}", x, y, plot.feed_rate)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Movements of the pen shared by all the outputs, in packing coordinates.
+#[derive(Debug, Clone, PartialEq)]
+enum PenMove {
+    /// Change to the pen of the given layer, with the pen up.
+    Layer {
+        index: usize,
+        name: String,
+    },
+
+    Up,
+    Down,
+
+    /// Move to the given point, always with the pen up.
+    Travel((f32, f32)),
+
+    /// Draw the given stroke from its start, which is where the pen is, always
+    /// with the pen down.
+    Draw(Stroke),
+}
+
+/// The pen is lifted before moving to the start of each stroke and lowered
+/// to draw it, then it's lifted again. It ends back at the origin of the
+/// plotter.
+fn pen_moves(bbox: &Bbox, layers: Vec<Layer>) -> Vec<PenMove> {
+    let mut moves = vec![PenMove::Up];
+
+    for (index, layer) in layers.into_iter().enumerate() {
+        moves.push(PenMove::Layer {
+            index,
+            name: layer.name,
+        });
+
+        for stroke in layer.strokes {
+            moves.push(PenMove::Travel(stroke.start()));
+            moves.push(PenMove::Down);
+            moves.push(PenMove::Draw(stroke));
+            moves.push(PenMove::Up);
+        }
+    }
+
+    // see `transform`
+    moves.push(PenMove::Travel((bbox.x0(), bbox.y1())));
+
+    moves
+}
+
+/// Map from packing coordinates to plotter coordinates with the bottom left
+/// corner of the drawing at the origin and the y axis pointing up.
+fn transform(bbox: &Bbox, scale: f32) -> impl Fn(f32, f32) -> (f32, f32) {
+    let bbox = bbox.clone();
+    move |x, y| ((x - bbox.x0()) * scale, (bbox.y1() - y) * scale)
+}
+
+/// Strokes filling the given packed shape, clipped so that they don't cross
+/// the shapes packed inside it.
+fn fill_strokes<C: Packable>(shape: &PackShape<C, C>, plot: &PlotSettings) -> Vec<Stroke> {
+    let lines = match plot.fill {
+        Fill::None => return vec![],
+        Fill::Hatch { spacing, angle } => hatch_lines(&shape.bbox(), spacing, angle),
+        Fill::Concentric { spacing } => {
+            let (x, y) = shape.center();
+            if spacing.is_nan() || spacing <= 0.0 {
+                return vec![];
+            }
+
+            (1..=MAX_FILL_LINES)
+                .map(|i| shape.scale() - i as f32 * spacing)
+                .take_while(|&scale| scale > 0.0)
+                .map(|scale| {
+                    let mut ring = shape.container.placed(x, y, scale).outline(plot.tolerance);
+                    ring.push(ring[0]);
+                    ring
+                })
+                .collect()
+        }
+    };
+
+    let inside = |x: f32, y: f32| {
+        shape.sdf(x, y) < 0.0
+            && shape
+                .index
+                .candidates(x, y)
+                .iter()
+                .all(|&i| shape.children[i].sdf(x, y) > 0.0)
+    };
+
+    lines
+        .iter()
+        .flat_map(|line| clip(line, plot.tolerance, inside))
+        .map(Stroke::Line)
+        .collect()
+}
+
+/// Parallel lines covering the given bbox, alternating their direction so
+/// that the end of a line is close to the start of the next one.
+fn hatch_lines(bbox: &Bbox, spacing: f32, angle: f32) -> Vec<Vec<(f32, f32)>> {
+    if spacing.is_nan() || spacing <= 0.0 {
+        return vec![];
+    }
+
+    let (s, c) = angle.sin_cos();
+    let corners = [
+        (bbox.x0(), bbox.y0()),
+        (bbox.x1(), bbox.y0()),
+        (bbox.x0(), bbox.y1()),
+        (bbox.x1(), bbox.y1()),
+    ];
+
+    // extents of the bbox along the direction of the lines and across them
+    let along = corners.iter().map(|&(x, y)| x * c + y * s);
+    let across = corners.iter().map(|&(x, y)| -x * s + y * c);
+    let (u0, u1) = along.fold((f32::INFINITY, f32::NEG_INFINITY), |(a, b), u| {
+        (a.min(u), b.max(u))
+    });
+    let (v0, v1) = across.fold((f32::INFINITY, f32::NEG_INFINITY), |(a, b), v| {
+        (a.min(v), b.max(v))
+    });
+
+    let point = |u: f32, v: f32| (u * c - v * s, u * s + v * c);
+
+    (0..MAX_FILL_LINES)
+        .map(|i| (i, v0 + (i as f32 + 0.5) * spacing))
+        .take_while(|&(_, v)| v < v1)
+        .map(|(i, v)| {
+            let mut line = vec![point(u0, v), point(u1, v)];
+            if i % 2 == 1 {
+                line.reverse();
+            }
+            line
+        })
+        .collect()
+}
+
+/// Pieces of the given polyline where `inside` holds, sampled every `step`.
+fn clip(line: &[(f32, f32)], step: f32, inside: impl Fn(f32, f32) -> bool) -> Vec<Vec<(f32, f32)>> {
+    let mut pieces = vec![];
+    let mut piece = vec![];
+
+    for w in line.windows(2) {
+        let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+        let n = (((x1 - x0).hypot(y1 - y0) / step).ceil() as usize).max(1);
+
+        // the first point of a segment is the last one of the previous one
+        let first = if pieces.is_empty() && piece.is_empty() {
+            0
+        } else {
+            1
+        };
+        for i in first..=n {
+            let t = i as f32 / n as f32;
+            let (x, y) = (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t);
+
+            if inside(x, y) {
+                piece.push((x, y));
+            } else if !piece.is_empty() {
+                pieces.push(std::mem::take(&mut piece));
+            }
+        }
+    }
+    if !piece.is_empty() {
+        pieces.push(piece);
+    }
+
+    pieces.retain(|p| p.len() >= 2);
+    for p in &mut pieces {
+        simplify(p);
+    }
+    pieces
+}
+
+/// Drop the points in the middle of straight runs.
+fn simplify(points: &mut Vec<(f32, f32)>) {
+    let mut out: Vec<(f32, f32)> = Vec::with_capacity(points.len());
+    for &p in points.iter() {
+        if out.len() >= 2 {
+            let (a, b) = (out[out.len() - 2], out[out.len() - 1]);
+            let cross = (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+            if cross.abs() <= 1e-3 * (p.0 - a.0).hypot(p.1 - a.1) {
+                out.pop();
+            }
+        }
+        out.push(p);
+    }
+    *points = out;
+}
+
+/// Closed strokes following the contour of the sdf of the given shape, used by
+/// shapes without an exact outline.
+pub fn contour_strokes(shape: &impl Shape) -> Vec<Stroke> {
+    let bbox = shape.bbox();
+    let step = bbox.width().max(bbox.height()) / CONTOUR_SAMPLES;
+
+    contour::contours(shape, step)
+        .into_iter()
+        .map(|mut c| {
+            c.push(c[0]);
+            Stroke::Line(c)
+        })
+        .collect()
+}
+
+/// Closed stroke through the given vertices.
+pub fn polygon_stroke(points: &[(f32, f32)]) -> Stroke {
+    let mut points = points.to_vec();
+    points.push(points[0]);
+    Stroke::Line(points)
+}
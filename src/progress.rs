@@ -0,0 +1,95 @@
+//! Hooks to follow a packing while it runs and to stop it early, see
+//! `pack_with_observer`.
+//!
+//! Observers are told about every shape that's placed and, every
+//! `PROGRESS_INTERVAL` attempts, about the overall progress. Both hooks can
+//! stop the packing by returning `ControlFlow::Break`, the shapes placed so far
+//! are kept.
+
+use std::{
+    ops::ControlFlow,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{PackShape, Packable};
+
+/// Number of attempts between two calls to `Observer::progress`.
+pub const PROGRESS_INTERVAL: usize = 100;
+
+/// Statistics of a packing run so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Number of shapes placed, at any depth.
+    pub placed: usize,
+
+    /// Number of points tried, placed or not.
+    pub attempts: usize,
+
+    /// Number of attempts since the last shape was placed.
+    pub stall: usize,
+
+    /// Fraction of the area of the root covered by its children.
+    pub coverage: f32,
+
+    /// Fraction of the last `PROGRESS_INTERVAL` attempts that placed a shape,
+    /// updated every `PROGRESS_INTERVAL` attempts. It starts at 1.
+    pub acceptance_rate: f32,
+
+    /// Time since the packing started.
+    pub elapsed: Duration,
+}
+
+pub trait Observer<C: Packable> {
+    /// Called after the given shape has been placed.
+    fn placed(&mut self, _shape: &PackShape<C, C>, _progress: &Progress) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called every `PROGRESS_INTERVAL` attempts.
+    fn progress(&mut self, _progress: &Progress) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Observer that stops the packing once the given instant has passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deadline(pub Instant);
+
+impl Deadline {
+    /// Deadline `budget` from now.
+    pub fn after(budget: Duration) -> Self {
+        Deadline(Instant::now() + budget)
+    }
+}
+
+/// Observer that does nothing.
+impl<C: Packable> Observer<C> for () {}
+
+/// Closures are only told about the progress.
+impl<C: Packable, F: FnMut(&Progress) -> ControlFlow<()>> Observer<C> for F {
+    fn progress(&mut self, progress: &Progress) -> ControlFlow<()> {
+        self(progress)
+    }
+}
+
+impl<C: Packable> Observer<C> for Deadline {
+    fn progress(&mut self, _progress: &Progress) -> ControlFlow<()> {
+        if Instant::now() >= self.0 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+/// A flag that stops the packing once it's set, e.g. from another thread.
+impl<C: Packable> Observer<C> for &AtomicBool {
+    fn progress(&mut self, _progress: &Progress) -> ControlFlow<()> {
+        if self.load(Ordering::Relaxed) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Software rasterizer for packings, an alternative to `dump_svg` that
+//! doesn't need an external tool to get an image.
+
+use std::io::Write;
+
+use image::{codecs::png::PngEncoder, ImageEncoder, ImageResult, RgbImage};
+
+use crate::{bounds, Bbox, PackShape, Packable, Settings, Shape};
+
+/// Half of the diagonal of a pixel, points closer than this to the boundary
+/// of a shape might be covered partially.
+const HALF_DIAGONAL: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Render the given roots with the same layout and colors of `dump_svg`.
+///
+/// The image has `scale` pixels per unit and edges are anti-aliased by
+/// sampling `supersampling * supersampling` points per pixel. Without roots
+/// it's a single pixel of the background color.
+pub fn render<S: Shape, C: Packable>(
+    roots: &[PackShape<S, C>],
+    cfg: &Settings,
+    scale: f32,
+    supersampling: u32,
+) -> RgbImage {
+    let bbox = bounds(roots).unwrap_or_else(|| Bbox::new(0.0, 0.0));
+
+    let width = (bbox.width() * scale).ceil().max(1.0) as u32;
+    let height = (bbox.height() * scale).ceil().max(1.0) as u32;
+
+    let mut canvas = Canvas {
+        image: RgbImage::from_pixel(width, height, image::Rgb(parse_color(&cfg.palette[0]))),
+        view: bbox,
+        scale,
+        supersampling: supersampling.max(1),
+    };
+
+    let mut stack = vec![];
+
+    for root in roots {
+        canvas.fill(root, root.fill_color(cfg));
+        stack.extend(root.children());
+    }
+
+    while let Some(c) = stack.pop() {
+        canvas.fill(c, c.fill_color(cfg));
+        stack.extend(c.children());
+    }
+
+    canvas.image
+}
+
+/// Render the given roots and write them as a PNG, see `render`.
+pub fn dump_png<S: Shape, C: Packable>(
+    out: &mut impl Write,
+    roots: &[PackShape<S, C>],
+    cfg: &Settings,
+    scale: f32,
+    supersampling: u32,
+) -> ImageResult<()> {
+    let image = render(roots, cfg, scale, supersampling);
+
+    PngEncoder::new(out).write_image(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        image::ExtendedColorType::Rgb8,
+    )
+}
+
+struct Canvas {
+    image: RgbImage,
+    view: Bbox,
+    scale: f32,
+    supersampling: u32,
+}
+
+impl Canvas {
+    /// Blend the given shape over the image.
+    ///
+    /// The sdf never overestimates the distance to the boundary, so pixels
+    /// whose center is far from it are skipped or filled in runs and only the
+    /// ones along the boundary are supersampled.
+    fn fill(&mut self, shape: &impl Shape, color: &str) {
+        let color = parse_color(color).map(f32::from);
+
+        let bbox = shape.bbox();
+        let (i0, j0) = self.to_pixel(bbox.x0(), bbox.y0());
+        let (i1, j1) = self.to_pixel(bbox.x1(), bbox.y1());
+
+        for j in j0..=j1 {
+            let mut i = i0;
+            while i <= i1 {
+                let (x, y) = self.to_point(i as f32 + 0.5, j as f32 + 0.5);
+                let d = shape.sdf(x, y) * self.scale;
+
+                if d >= HALF_DIAGONAL {
+                    i += ((d - HALF_DIAGONAL) as u32).max(1);
+                    continue;
+                }
+
+                if d <= -HALF_DIAGONAL {
+                    let run = ((-d - HALF_DIAGONAL) as u32).max(1);
+                    for i in i..(i + run).min(i1 + 1) {
+                        self.blend(i, j, color, 1.0);
+                    }
+                    i += run;
+                    continue;
+                }
+
+                let coverage = self.coverage(shape, i, j);
+                self.blend(i, j, color, coverage);
+                i += 1;
+            }
+        }
+    }
+
+    fn coverage(&self, shape: &impl Shape, i: u32, j: u32) -> f32 {
+        let n = self.supersampling;
+
+        let mut inside = 0;
+        for sj in 0..n {
+            for si in 0..n {
+                let (x, y) = self.to_point(
+                    i as f32 + (si as f32 + 0.5) / n as f32,
+                    j as f32 + (sj as f32 + 0.5) / n as f32,
+                );
+                if shape.sdf(x, y) <= 0.0 {
+                    inside += 1;
+                }
+            }
+        }
+
+        inside as f32 / (n * n) as f32
+    }
+
+    fn blend(&mut self, i: u32, j: u32, color: [f32; 3], alpha: f32) {
+        if alpha <= 0.0 {
+            return;
+        }
+
+        let pixel = self.image.get_pixel_mut(i, j);
+        for (p, c) in pixel.0.iter_mut().zip(color) {
+            *p = (f32::from(*p) * (1.0 - alpha) + c * alpha).round() as u8;
+        }
+    }
+
+    /// Pixel containing the given point, clamped to the image.
+    fn to_pixel(&self, x: f32, y: f32) -> (u32, u32) {
+        let u = (x - self.view.x0()) * self.scale;
+        let v = (y - self.view.y0()) * self.scale;
+
+        (
+            (u.max(0.0) as u32).min(self.image.width() - 1),
+            (v.max(0.0) as u32).min(self.image.height() - 1),
+        )
+    }
+
+    /// Point at the given position in pixels.
+    fn to_point(&self, u: f32, v: f32) -> (f32, f32) {
+        (
+            self.view.x0() + u / self.scale,
+            self.view.y0() + v / self.scale,
+        )
+    }
+}
+
+/// Parse a `#rrggbb` color, anything else is black.
+pub(crate) fn parse_color(color: &str) -> [u8; 3] {
+    let channel = |i: usize| {
+        color
+            .get(1 + 2 * i..3 + 2 * i)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .unwrap_or(0)
+    };
+
+    [channel(0), channel(1), channel(2)]
+}
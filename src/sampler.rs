@@ -0,0 +1,379 @@
+//! Sources of the points where `pack` tries to place shapes, see
+//! `Settings::sampler`.
+//!
+//! All the samplers but the uniform one generate points over the bbox of the
+//! root and reject the ones outside of it. Low-discrepancy sequences, jittered
+//! grids and Poisson-disk sampling spread the points more evenly than plain
+//! random points which leaves fewer gaps for the late attempts to find.
+
+use std::{fmt, str::FromStr};
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{Bbox, Shape};
+
+/// Number of points outside the root in a row after which a random point
+/// inside it is taken instead.
+const MAX_REJECTIONS: usize = 10_000;
+
+/// Maximum number of points of a jittered grid or Poisson-disk pass, the
+/// spacing isn't reduced any further once it's reached.
+const MAX_PASS_POINTS: usize = 1 << 22;
+
+/// Bridson's number of attempts to place a point around an active one.
+const POISSON_ATTEMPTS: usize = 30;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateSampler {
+    /// Random points from `Shape::random_point`.
+    #[default]
+    Uniform,
+
+    /// Halton sequence in bases 2 and 3, randomly shifted.
+    Halton,
+
+    /// Sobol sequence, randomly scrambled.
+    Sobol,
+
+    /// A random point in each cell of a grid with the given spacing, visited
+    /// in random order. Each time all the cells have been visited the spacing
+    /// is halved, down to the minimum radius. It's 4 times the minimum radius
+    /// if missing.
+    Jitter { spacing: Option<f32> },
+
+    /// Random points at least the given radius apart, in random order. Each
+    /// time all the points have been tried the radius is halved, down to the
+    /// minimum radius. It's 4 times the minimum radius if missing.
+    Poisson { radius: Option<f32> },
+}
+
+/// Points generated by a `CandidateSampler`, see `CandidateSampler::candidates`.
+#[derive(Debug, Clone)]
+pub struct Candidates {
+    bbox: Bbox,
+    state: State,
+}
+
+#[derive(Debug, Clone)]
+enum State {
+    Uniform,
+    Halton {
+        i: u32,
+        offset: (f32, f32),
+    },
+    Sobol {
+        i: u32,
+        x: (u32, u32),
+        scramble: (u32, u32),
+    },
+    Pass {
+        jitter: bool,
+        spacing: f32,
+        min_spacing: f32,
+        points: Vec<(f32, f32)>,
+    },
+}
+
+impl CandidateSampler {
+    /// Start generating points over the given bbox, `min_radius` is the
+    /// minimum radius of the packed shapes.
+    pub fn candidates(&self, bbox: &Bbox, min_radius: f32, rng: &mut impl Rng) -> Candidates {
+        let state = match *self {
+            CandidateSampler::Uniform => State::Uniform,
+            CandidateSampler::Halton => State::Halton {
+                i: 0,
+                offset: (rng.gen(), rng.gen()),
+            },
+            CandidateSampler::Sobol => State::Sobol {
+                i: 0,
+                x: (0, 0),
+                scramble: (rng.gen(), rng.gen()),
+            },
+            CandidateSampler::Jitter { spacing } => State::pass(true, spacing, bbox, min_radius),
+            CandidateSampler::Poisson { radius } => State::pass(false, radius, bbox, min_radius),
+        };
+
+        Candidates {
+            bbox: bbox.clone(),
+            state,
+        }
+    }
+}
+
+impl State {
+    fn pass(jitter: bool, spacing: Option<f32>, bbox: &Bbox, min_radius: f32) -> Self {
+        let mut spacing = spacing.unwrap_or(4.0 * min_radius);
+        // without a minimum radius there's no spacing to start from, nor to
+        // double until the pass is small enough
+        if !(spacing.is_finite() && spacing > 0.0) {
+            spacing = bbox.width().max(bbox.height()).max(1.0);
+        }
+        while pass_points(bbox, spacing) > MAX_PASS_POINTS {
+            spacing *= 2.0;
+        }
+
+        State::Pass {
+            jitter,
+            spacing,
+            min_spacing: min_radius,
+            points: vec![],
+        }
+    }
+}
+
+impl Candidates {
+    /// Next point inside the given shape, which should be the one whose bbox
+    /// was given to `CandidateSampler::candidates`.
+    pub fn next_in(&mut self, shape: &impl Shape, rng: &mut impl Rng) -> (f32, f32) {
+        if let State::Uniform = self.state {
+            return shape.random_point(rng);
+        }
+
+        for _ in 0..MAX_REJECTIONS {
+            let (x, y) = self.next(rng);
+            if shape.sdf(x, y) < 0.0 {
+                return (x, y);
+            }
+        }
+
+        shape.random_point(rng)
+    }
+
+    /// Next point in the bbox.
+    fn next(&mut self, rng: &mut impl Rng) -> (f32, f32) {
+        let (u, v) = match &mut self.state {
+            State::Uniform => (rng.gen(), rng.gen()),
+            State::Halton { i, offset } => {
+                *i = i.wrapping_add(1);
+                (
+                    (radical_inverse(*i, 2) + offset.0).fract(),
+                    (radical_inverse(*i, 3) + offset.1).fract(),
+                )
+            }
+            State::Sobol { i, x, scramble } => {
+                // Gray code order, each point differs from the previous one by
+                // the direction numbers of the lowest zero bit of the index
+                let c = i.trailing_ones().min(31);
+                x.0 ^= 1 << (31 - c);
+                x.1 ^= sobol_direction(c);
+                *i = i.wrapping_add(1);
+
+                let scale = 1.0 / 2f64.powi(32);
+                (
+                    (f64::from(x.0 ^ scramble.0) * scale) as f32,
+                    (f64::from(x.1 ^ scramble.1) * scale) as f32,
+                )
+            }
+            State::Pass {
+                jitter,
+                spacing,
+                min_spacing,
+                points,
+            } => {
+                if points.is_empty() {
+                    *points = if *jitter {
+                        jittered_grid(&self.bbox, *spacing, rng)
+                    } else {
+                        poisson_disk(&self.bbox, *spacing, rng)
+                    };
+
+                    let next = *spacing / 2.0;
+                    if next >= *min_spacing && pass_points(&self.bbox, next) <= MAX_PASS_POINTS {
+                        *spacing = next;
+                    }
+                }
+
+                return points.pop().unwrap_or_else(|| {
+                    (
+                        self.bbox.x0() + rng.gen::<f32>() * self.bbox.width(),
+                        self.bbox.y0() + rng.gen::<f32>() * self.bbox.height(),
+                    )
+                });
+            }
+        };
+
+        (
+            self.bbox.x0() + u * self.bbox.width(),
+            self.bbox.y0() + v * self.bbox.height(),
+        )
+    }
+}
+
+/// The digits of `i` in the given base mirrored around the decimal point.
+fn radical_inverse(mut i: u32, base: u32) -> f32 {
+    let inv_base = 1.0 / base as f64;
+    let (mut r, mut f) = (0.0, inv_base);
+    while i > 0 {
+        r += f64::from(i % base) * f;
+        i /= base;
+        f *= inv_base;
+    }
+    r as f32
+}
+
+/// Direction number of the second dimension of the Sobol sequence for the
+/// given bit, whose primitive polynomial is x + 1.
+fn sobol_direction(bit: u32) -> u32 {
+    let mut v = 1u32 << 31;
+    for _ in 0..bit {
+        v ^= v >> 1;
+    }
+    v
+}
+
+/// Approximate number of points of a pass over the given bbox.
+fn pass_points(bbox: &Bbox, spacing: f32) -> usize {
+    ((bbox.width() / spacing).ceil() * (bbox.height() / spacing).ceil()) as usize
+}
+
+/// A random point in each cell of a grid over the bbox, in random order.
+fn jittered_grid(bbox: &Bbox, spacing: f32, rng: &mut impl Rng) -> Vec<(f32, f32)> {
+    let cols = (bbox.width() / spacing).ceil() as usize;
+    let rows = (bbox.height() / spacing).ceil() as usize;
+
+    let mut points = Vec::with_capacity(cols * rows);
+    for r in 0..rows {
+        for c in 0..cols {
+            points.push((
+                bbox.x0() + (c as f32 + rng.gen::<f32>()) * spacing,
+                bbox.y0() + (r as f32 + rng.gen::<f32>()) * spacing,
+            ));
+        }
+    }
+
+    points.shuffle(rng);
+    points
+}
+
+/// Points in the bbox at least `radius` apart with Bridson's algorithm, in
+/// random order.
+fn poisson_disk(bbox: &Bbox, radius: f32, rng: &mut impl Rng) -> Vec<(f32, f32)> {
+    let cell = radius / 2f32.sqrt();
+    let cols = (bbox.width() / cell).ceil().max(1.0) as usize;
+    let rows = (bbox.height() / cell).ceil().max(1.0) as usize;
+    let cell_of = |(x, y): (f32, f32)| {
+        let c = (((x - bbox.x0()) / cell) as usize).min(cols - 1);
+        let r = (((y - bbox.y0()) / cell) as usize).min(rows - 1);
+        (c, r)
+    };
+
+    // each cell holds at most one point since its diagonal is the radius
+    let mut grid = vec![usize::MAX; cols * rows];
+    let mut points = vec![];
+    let mut active = vec![];
+
+    let first = (
+        bbox.x0() + rng.gen::<f32>() * bbox.width(),
+        bbox.y0() + rng.gen::<f32>() * bbox.height(),
+    );
+    let (c, r) = cell_of(first);
+    grid[r * cols + c] = 0;
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let a = rng.gen_range(0..active.len());
+        let (ax, ay) = points[active[a]];
+
+        let candidate = (0..POISSON_ATTEMPTS).find_map(|_| {
+            let angle = rng.gen::<f32>() * std::f32::consts::TAU;
+            let d = radius * (1.0 + rng.gen::<f32>());
+            let p = (ax + d * angle.cos(), ay + d * angle.sin());
+            if p.0 < bbox.x0() || p.0 > bbox.x1() || p.1 < bbox.y0() || p.1 > bbox.y1() {
+                return None;
+            }
+
+            let (c, r) = cell_of(p);
+            let near = (r.saturating_sub(2)..(r + 3).min(rows))
+                .flat_map(|r| (c.saturating_sub(2)..(c + 3).min(cols)).map(move |c| (c, r)))
+                .map(|(c, r)| grid[r * cols + c])
+                .filter(|&i| i != usize::MAX)
+                .any(|i| {
+                    let (x, y) = points[i];
+                    (x - p.0).powi(2) + (y - p.1).powi(2) < radius * radius
+                });
+
+            if near {
+                None
+            } else {
+                Some(p)
+            }
+        });
+
+        match candidate {
+            Some(p) => {
+                let (c, r) = cell_of(p);
+                grid[r * cols + c] = points.len();
+                active.push(points.len());
+                points.push(p);
+            }
+            None => {
+                active.swap_remove(a);
+            }
+        }
+    }
+
+    points.shuffle(rng);
+    points
+}
+
+impl fmt::Display for CandidateSampler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CandidateSampler::Uniform => write!(f, "uniform"),
+            CandidateSampler::Halton => write!(f, "halton"),
+            CandidateSampler::Sobol => write!(f, "sobol"),
+            CandidateSampler::Jitter { spacing: None } => write!(f, "jitter"),
+            CandidateSampler::Jitter { spacing: Some(s) } => write!(f, "jitter:{}", s),
+            CandidateSampler::Poisson { radius: None } => write!(f, "poisson"),
+            CandidateSampler::Poisson { radius: Some(r) } => write!(f, "poisson:{}", r),
+        }
+    }
+}
+
+impl FromStr for CandidateSampler {
+    type Err = String;
+
+    /// Parse `uniform`, `halton`, `sobol`, `jitter[:<spacing>]` or
+    /// `poisson[:<radius>]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let kind = parts.next().unwrap_or("");
+        let mut number = |name: &str| {
+            parts
+                .next()
+                .map(|n| {
+                    n.parse()
+                        .ok()
+                        .filter(|&n: &f32| n.is_finite() && n > 0.0)
+                        .ok_or_else(|| format!("invalid {} {}", name, n))
+                })
+                .transpose()
+        };
+
+        let sampler = match kind {
+            "uniform" => CandidateSampler::Uniform,
+            "halton" => CandidateSampler::Halton,
+            "sobol" => CandidateSampler::Sobol,
+            "jitter" => CandidateSampler::Jitter {
+                spacing: number("spacing")?,
+            },
+            "poisson" => CandidateSampler::Poisson {
+                radius: number("radius")?,
+            },
+            _ => {
+                return Err(format!(
+                    "unknown sampler {}, use uniform, halton, sobol, jitter[:<spacing>] or poisson[:<radius>]",
+                    s
+                ))
+            }
+        };
+
+        match parts.next() {
+            Some(extra) => Err(format!("unexpected {} in {}", extra, s)),
+            None => Ok(sampler),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Scene files describing a whole packing run: the containers with their holes
+//! and colors, the settings, the seed and the outputs.
+//!
+//! Scenes are written in TOML, or in JSON if the file ends with `.json`, for
+//! example
+//!
+//! ```toml
+//! seed = 42
+//! outputs = ["packing.svg", "packing.png"]
+//!
+//! [settings]
+//! min_radius = 3.0
+//! palette = ["#172a89", "#f7f7f3", "#ee5d65"]
+//!
+//! [[containers]]
+//! shape = "rect"
+//! x = 0.0
+//! y = 0.0
+//! width = 800.0
+//! height = 600.0
+//! holes = [{ shape = "circle", x = 400.0, y = 300.0, radius = 100.0 }]
+//!
+//! [[containers]]
+//! shape = "svg"
+//! path = "logo.svg"
+//! palette = ["#000000", "#ffffff"]
+//! ```
+
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    svg::{self, SvgError},
+    Circle, PackShape, Packable, Polyline, Settings, Shape,
+};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    Svg(SvgError),
+
+    /// A shape has too few points or no area.
+    InvalidShape(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    /// Seed of the random number generator, random if missing.
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    #[serde(default)]
+    pub settings: Settings,
+
+    pub containers: Vec<Container>,
+
+    /// Paths the packing is written to, the format is chosen by extension.
+    #[serde(default)]
+    pub outputs: Vec<PathBuf>,
+}
+
+/// A container to pack, with optional holes and colors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Container {
+    #[serde(flatten)]
+    pub shape: ShapeDesc,
+
+    /// Areas removed from the container.
+    #[serde(default)]
+    pub holes: Vec<ShapeDesc>,
+
+    /// Color of the container itself, the second color of the palette if
+    /// missing.
+    #[serde(default)]
+    pub color: Option<Color>,
+
+    /// Palette used by the shapes packed in this container instead of the one
+    /// in the settings.
+    #[serde(default)]
+    pub palette: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "lowercase")]
+pub enum ShapeDesc {
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    Circle {
+        x: f32,
+        y: f32,
+        radius: f32,
+    },
+
+    /// A closed polygon.
+    Polyline {
+        points: Vec<(f32, f32)>,
+    },
+
+    /// All the shapes of an SVG file, see `svg::load`.
+    Svg {
+        path: PathBuf,
+        #[serde(default = "default_tolerance")]
+        tolerance: f32,
+    },
+}
+
+/// Either the index of a color in the palette or the color itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Color {
+    Index(usize),
+    Value(String),
+}
+
+fn default_tolerance() -> f32 {
+    0.5
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(e) => write!(f, "cannot read scene: {}", e),
+            SceneError::Toml(e) => write!(f, "invalid scene: {}", e),
+            SceneError::Json(e) => write!(f, "invalid scene: {}", e),
+            SceneError::Svg(e) => write!(f, "cannot import svg: {}", e),
+            SceneError::InvalidShape(s) => write!(f, "invalid shape: {}", s),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(e) => Some(e),
+            SceneError::Toml(e) => Some(e),
+            SceneError::Json(e) => Some(e),
+            SceneError::Svg(e) => Some(e),
+            SceneError::InvalidShape(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(e: io::Error) -> Self {
+        SceneError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for SceneError {
+    fn from(e: toml::de::Error) -> Self {
+        SceneError::Toml(e)
+    }
+}
+
+impl From<serde_json::Error> for SceneError {
+    fn from(e: serde_json::Error) -> Self {
+        SceneError::Json(e)
+    }
+}
+
+impl From<SvgError> for SceneError {
+    fn from(e: SvgError) -> Self {
+        SceneError::Svg(e)
+    }
+}
+
+impl Scene {
+    /// Read the scene at the given path, as JSON if it ends with `.json` and
+    /// as TOML otherwise. Relative paths of SVG containers are resolved
+    /// against the directory of the scene.
+    pub fn load(path: impl AsRef<Path>) -> Result<Scene, SceneError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)?;
+
+        let mut scene: Scene = if path.extension().is_some_and(|e| e == "json") {
+            serde_json::from_str(&data)?
+        } else {
+            toml::from_str(&data)?
+        };
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let shapes = scene
+            .containers
+            .iter_mut()
+            .flat_map(|c| std::iter::once(&mut c.shape).chain(c.holes.iter_mut()));
+        for shape in shapes {
+            if let ShapeDesc::Svg { path, .. } = shape {
+                *path = dir.join(&*path);
+            }
+        }
+
+        Ok(scene)
+    }
+}
+
+impl Container {
+    /// Shapes to pack for this container, one for each piece that's left after
+    /// cutting the holes. SVG containers might have more pieces to begin with.
+    pub fn shapes(&self) -> Result<Vec<Polyline>, SceneError> {
+        let mut pieces = self.shape.polylines()?;
+
+        for hole in &self.holes {
+            for hole in hole.polylines()? {
+                pieces = pieces
+                    .into_iter()
+                    .flat_map(|mut piece| {
+                        if piece.push_hole(hole.clone()) {
+                            vec![piece]
+                        } else {
+                            // either the hole doesn't overlap the piece or it
+                            // splits it
+                            piece.difference(&hole)
+                        }
+                    })
+                    .collect();
+            }
+        }
+
+        Ok(pieces)
+    }
+
+    /// Set the color of a root created from one of the shapes of this
+    /// container.
+    pub fn style<S: Shape, C: Packable>(&self, root: &mut PackShape<S, C>, cfg: &Settings) {
+        let palette = self.palette.as_ref().unwrap_or(&cfg.palette);
+
+        match &self.color {
+            None => root.color = 1 % palette.len(),
+            Some(Color::Index(i)) => root.color = i % palette.len(),
+            Some(Color::Value(c)) => root.fill = Some(c.clone()),
+        }
+
+        if self.palette.is_some() && root.fill.is_none() {
+            root.fill = Some(palette[root.color].clone());
+        }
+    }
+
+    /// Color the shapes packed in the given root with the palette of this
+    /// container, if it has one. The color indices picked while packing are
+    /// kept so the colors cycle as they do with the palette of the settings.
+    pub fn apply_palette<S: Shape, C: Packable>(&self, root: &mut PackShape<S, C>) {
+        let palette = match &self.palette {
+            Some(palette) => palette,
+            None => return,
+        };
+
+        let mut stack = root.children.iter_mut().collect::<Vec<_>>();
+        while let Some(shape) = stack.pop() {
+            if shape.fill.is_none() {
+                shape.fill = Some(palette[shape.color % palette.len()].clone());
+            }
+            stack.extend(shape.children.iter_mut());
+        }
+    }
+}
+
+impl ShapeDesc {
+    fn polylines(&self) -> Result<Vec<Polyline>, SceneError> {
+        let invalid = || SceneError::InvalidShape(format!("{:?}", self));
+
+        let points = match self {
+            ShapeDesc::Rect {
+                x,
+                y,
+                width,
+                height,
+            } => vec![
+                (*x, *y),
+                (x + width, *y),
+                (x + width, y + height),
+                (*x, y + height),
+            ],
+            ShapeDesc::Circle { x, y, radius } => {
+                // points this far apart keep the chords within the tolerance
+                let step = (8.0 * radius * default_tolerance()).sqrt();
+                Circle::new(*x, *y, *radius).outline(step)
+            }
+            ShapeDesc::Polyline { points } => points.clone(),
+            ShapeDesc::Svg { path, tolerance } => return Ok(svg::load(path, *tolerance)?),
+        };
+
+        match Polyline::new(points) {
+            Some(p) if p.area() > 0.0 => Ok(vec![p]),
+            _ => Err(invalid()),
+        }
+    }
+}
@@ -1,13 +1,22 @@
 use std::{
-    f32::consts::PI,
+    f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2, PI},
     io::{self, Write},
 };
 
+use i_overlay::{
+    core::{fill_rule::FillRule, overlay_rule::OverlayRule},
+    float::single::SingleFloatOverlay,
+};
 use rand::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::Shape;
+use crate::{
+    pdf,
+    plot::{self, Stroke},
+    Packable, Shape,
+};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Bbox {
     x0: f32,
     y0: f32,
@@ -15,14 +24,91 @@ pub struct Bbox {
     y1: f32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Circle {
     pub x: f32,
     pub y: f32,
     pub radius: f32,
 }
 
-#[derive(Clone, Debug)]
+/// Regular polygon with the given number of sides inscribed in the circle of
+/// the given radius. With no rotation, one of the edges is perpendicular to the
+/// x axis which means that squares are axis aligned.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegularPolygon {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub sides: u32,
+    pub rotation: f32,
+}
+
+/// Axis aligned ellipse.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ellipse {
+    pub x: f32,
+    pub y: f32,
+    pub rx: f32,
+    pub ry: f32,
+}
+
+/// Rectangle whose corners are rounded with the given radius.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoundedRect {
+    bbox: Bbox,
+    radius: f32,
+}
+
+/// Star with the given number of points whose tips lie on the circle of radius
+/// `outer` and whose inner vertices lie on the circle of radius `inner`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Star {
+    pub x: f32,
+    pub y: f32,
+    pub outer: f32,
+    pub inner: f32,
+    pub points: u32,
+    pub rotation: f32,
+}
+
+/// Segment between two points thickened by the given radius.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Capsule {
+    pub a: (f32, f32),
+    pub b: (f32, f32),
+    pub radius: f32,
+}
+
+/// Ring between two concentric circles.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Annulus {
+    pub x: f32,
+    pub y: f32,
+    pub inner: f32,
+    pub outer: f32,
+}
+
+/// Circular sector going from angle `start` to angle `end`, in radians.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pie {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub start: f32,
+    pub end: f32,
+}
+
+/// Axis aligned superellipse, that is the curve `|x/rx|^n + |y/ry|^n = 1`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Superellipse {
+    pub x: f32,
+    pub y: f32,
+    pub rx: f32,
+    pub ry: f32,
+    pub n: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Polyline {
     points: Vec<(f32, f32)>,
     holes: Vec<Polyline>,
@@ -48,12 +134,49 @@ impl Bbox {
         self.y1 = self.y1.max(y);
     }
 
+    /// Smallest bbox containing both this bbox and the other one.
+    pub fn union(&self, other: &Bbox) -> Bbox {
+        let mut bbox = self.clone();
+        bbox.expand(other.x0, other.y0);
+        bbox.expand(other.x1, other.y1);
+        bbox
+    }
+
+    /// Area shared by this bbox and the other one, it's empty if they don't
+    /// overlap.
+    pub fn intersection(&self, other: &Bbox) -> Bbox {
+        let x0 = self.x0.max(other.x0);
+        let y0 = self.y0.max(other.y0);
+        Bbox {
+            x0,
+            y0,
+            x1: self.x1.min(other.x1).max(x0),
+            y1: self.y1.min(other.y1).max(y0),
+        }
+    }
+
+    /// Whether this bbox and the other one share some area.
+    pub fn overlaps(&self, other: &Bbox) -> bool {
+        self.x0 < other.x1 && other.x0 < self.x1 && self.y0 < other.y1 && other.y0 < self.y1
+    }
+
+    /// Whether the given bbox is completely inside this one.
+    pub fn contains(&self, other: &Bbox) -> bool {
+        self.x0 <= other.x0 && self.y0 <= other.y0 && self.x1 >= other.x1 && self.y1 >= other.y1
+    }
+
     pub fn x0(&self) -> f32 {
         self.x0
     }
     pub fn y0(&self) -> f32 {
         self.y0
     }
+    pub fn x1(&self) -> f32 {
+        self.x1
+    }
+    pub fn y1(&self) -> f32 {
+        self.y1
+    }
     pub fn width(&self) -> f32 {
         self.x1 - self.x0
     }
@@ -104,6 +227,26 @@ impl Shape for Bbox {
             stroke
         )
     }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(
+            w,
+            "{} {} {} {} re",
+            self.x0,
+            self.y0,
+            self.width(),
+            self.height()
+        )
+    }
+
+    fn strokes(&self, _tolerance: f32) -> Vec<Stroke> {
+        vec![plot::polygon_stroke(&[
+            (self.x0, self.y0),
+            (self.x1(), self.y0),
+            (self.x1(), self.y1()),
+            (self.x0, self.y1()),
+        ])]
+    }
 }
 
 impl Circle {
@@ -134,10 +277,10 @@ impl Shape for Circle {
 
     fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
         let a = rng.gen_range(0.0..2.0 * PI);
-        let d = rng.gen_range(0.0..self.radius);
+        let d = self.radius * rng.gen::<f32>().sqrt();
 
-        let x = a.cos() * d;
-        let y = a.sin() * d;
+        let x = self.x + a.cos() * d;
+        let y = self.y + a.sin() * d;
 
         (x, y)
     }
@@ -149,127 +292,339 @@ impl Shape for Circle {
             self.x, self.y, self.radius, fill, stroke
         )
     }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        pdf::write_ellipse(w, self.x, self.y, self.radius, self.radius)
+    }
+
+    fn strokes(&self, _tolerance: f32) -> Vec<Stroke> {
+        vec![Stroke::Circle {
+            x: self.x,
+            y: self.y,
+            radius: self.radius,
+        }]
+    }
 }
 
-impl Polyline {
-    pub fn new(points: Vec<(f32, f32)>) -> Option<Self> {
-        if points.is_empty() {
-            None
-        } else {
-            let (mut cx, mut cy) = points[0];
-            let mut bbox = Bbox::new(cx, cy);
-            for &(x, y) in points.iter().skip(1) {
-                bbox.expand(x, y);
-                cx += x;
-                cy += y;
-            }
-            cx /= points.len() as f32;
-            cy /= points.len() as f32;
-            Some(Self {
-                points,
-                cx,
-                cy,
-                bbox,
-                holes: vec![],
-            })
-        }
+impl Packable for Circle {
+    fn placed(&self, x: f32, y: f32, scale: f32) -> Self {
+        Circle::new(x, y, scale)
     }
 
-    /// as of now, hole must be completely contained in the polyline otherwise
-    /// it won't be added to the list of holes. This is to ensure the naive area
-    /// calculation is correct.
-    //
-    // TODO: clipping
-    pub fn push_hole(&mut self, hole: Polyline) -> bool {
-        let completely_contained = hole.points.iter().all(|&(x, y)| self.sdf(x, y) < 0.0);
-        if !completely_contained {
-            return false;
-        }
+    fn scale(&self) -> f32 {
+        self.radius
+    }
 
-        self.holes.push(hole);
-        true
+    fn outer_ratio(&self) -> f32 {
+        1.0
     }
 
-    fn get_d(&self) -> String {
-        let mut d = format!("M {},{}", self.points[0].0, self.points[0].1);
-        for &(x, y) in self.points.iter().skip(1) {
-            d += &format!("L {},{}", x, y);
-        }
-        d += "Z";
+    fn outline(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        let n = ((2.0 * PI * self.radius / tolerance).ceil() as usize).max(3);
+        (0..n)
+            .map(|i| {
+                let a = 2.0 * PI * i as f32 / n as f32;
+                (
+                    self.x + a.cos() * self.radius,
+                    self.y + a.sin() * self.radius,
+                )
+            })
+            .collect()
+    }
+}
 
-        for hole in &self.holes {
-            d += &hole.get_d();
+impl RegularPolygon {
+    pub fn new(x: f32, y: f32, radius: f32, sides: u32) -> Self {
+        assert!(sides >= 3);
+
+        Self {
+            x,
+            y,
+            radius,
+            sides,
+            rotation: 0.0,
         }
+    }
 
-        d
+    /// Distance between the center and the edges.
+    pub fn apothem(&self) -> f32 {
+        self.radius * (PI / self.sides as f32).cos()
+    }
+
+    pub fn vertices(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        let an = 2.0 * PI / self.sides as f32;
+        (0..self.sides).map(move |i| {
+            let a = self.rotation + an / 2.0 + an * i as f32;
+            (
+                self.x + a.cos() * self.radius,
+                self.y + a.sin() * self.radius,
+            )
+        })
     }
 }
 
-impl Shape for Polyline {
+impl Shape for RegularPolygon {
     fn bbox(&self) -> Bbox {
-        let mut bbox = Bbox::new(self.points[0].0, self.points[0].1);
-        for &(x, y) in self.points.iter().skip(1) {
+        let mut vertices = self.vertices();
+        let (x0, y0) = vertices.next().unwrap();
+        let mut bbox = Bbox::new(x0, y0);
+        for (x, y) in vertices {
             bbox.expand(x, y);
         }
         bbox
     }
 
     fn center(&self) -> (f32, f32) {
-        (self.cx, self.cy)
+        (self.x, self.y)
     }
 
     fn sdf(&self, x: f32, y: f32) -> f32 {
-        let (x0, y0) = self.points[0];
-        let mut d = (x - x0).powi(2) + (y - y0).powi(2);
-        let mut s = 1.0;
+        let an = 2.0 * PI / self.sides as f32;
+        let apothem = self.apothem();
+        let half_edge = apothem * (an / 2.0).tan();
 
-        for i in 0..self.points.len() {
-            let j = (i + self.points.len() - 1) % self.points.len();
+        // rotate the point into the sector centered on the positive x axis
+        let (s, c) = (-self.rotation).sin_cos();
+        let (px, py) = (x - self.x, y - self.y);
+        let (px, py) = (px * c - py * s, px * s + py * c);
+
+        let bn = an * ((py.atan2(px) + an / 2.0) / an).floor();
+        let (s, c) = (-bn).sin_cos();
+        let (px, py) = (px * c - py * s, px * s + py * c);
+
+        let (dx, dy) = (px - apothem, py - py.clamp(-half_edge, half_edge));
+        (dx * dx + dy * dy).sqrt().copysign(dx)
+    }
+
+    fn area(&self) -> f32 {
+        let apothem = self.apothem();
+        self.sides as f32 * apothem * apothem * (PI / self.sides as f32).tan()
+    }
+
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        // all the triangles between the center and an edge have the same area
+        let i = rng.gen_range(0..self.sides);
+        let an = 2.0 * PI / self.sides as f32;
+        let a0 = self.rotation + an / 2.0 + an * i as f32;
+        let a1 = a0 + an;
+
+        random_point_in_triangle(
+            rng,
+            (self.x, self.y),
+            (
+                self.x + a0.cos() * self.radius,
+                self.y + a0.sin() * self.radius,
+            ),
+            (
+                self.x + a1.cos() * self.radius,
+                self.y + a1.sin() * self.radius,
+            ),
+        )
+    }
+
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        let points = self
+            .vertices()
+            .map(|(x, y)| format!("{},{}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(
+            w,
+            r#"<polygon points="{}" fill="{}" stroke="{}"/>"#,
+            points, fill, stroke
+        )
+    }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        pdf::write_polygon(w, &self.vertices().collect::<Vec<_>>())
+    }
+
+    fn strokes(&self, _tolerance: f32) -> Vec<Stroke> {
+        vec![plot::polygon_stroke(&self.vertices().collect::<Vec<_>>())]
+    }
+}
+
+impl Packable for RegularPolygon {
+    fn placed(&self, x: f32, y: f32, scale: f32) -> Self {
+        Self {
+            x,
+            y,
+            radius: scale / (PI / self.sides as f32).cos(),
+            ..self.clone()
+        }
+    }
 
-            let (ix, iy) = self.points[i];
-            let (jx, jy) = self.points[j];
-            let (ex, ey) = (jx - ix, jy - iy);
-            let (wx, wy) = (x - ix, y - iy);
+    fn scale(&self) -> f32 {
+        self.apothem()
+    }
 
-            let t = ((wx * ex + wy * ey) / (ex.powi(2) + ey.powi(2)))
-                .max(0.0)
-                .min(1.0);
+    fn outer_ratio(&self) -> f32 {
+        1.0 / (PI / self.sides as f32).cos()
+    }
 
-            let (bx, by) = (wx - ex * t, wy - ey * t);
+    fn outline(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        let vertices = self.vertices().collect::<Vec<_>>();
 
-            d = d.min(bx.powi(2) + by.powi(2));
+        let mut points = vec![];
+        for (i, &(x0, y0)) in vertices.iter().enumerate() {
+            let (x1, y1) = vertices[(i + 1) % vertices.len()];
+            let len = (x1 - x0).hypot(y1 - y0);
+            let n = ((len / tolerance).ceil() as usize).max(1);
 
-            let a = y >= iy;
-            let b = y < jy;
-            let c = ex * wy > ey * wx;
-            if (a && b && c) || (!a && !b && !c) {
-                s *= -1.0;
+            for j in 0..n {
+                let t = j as f32 / n as f32;
+                points.push((x0 + (x1 - x0) * t, y0 + (y1 - y0) * t));
             }
         }
 
-        let mut d = s * d.sqrt();
-        for h in &self.holes {
-            let dd = h.sdf(x, y);
-            d = d.max(-dd);
+        points
+    }
+}
+
+impl Ellipse {
+    pub fn new(x: f32, y: f32, rx: f32, ry: f32) -> Self {
+        Self { x, y, rx, ry }
+    }
+
+    fn point_at(&self, t: f32) -> (f32, f32) {
+        (self.x + self.rx * t.cos(), self.y + self.ry * t.sin())
+    }
+}
+
+impl Shape for Ellipse {
+    fn bbox(&self) -> Bbox {
+        let mut bbox = Bbox::new(self.x - self.rx, self.y - self.ry);
+        bbox.expand(self.x + self.rx, self.y + self.ry);
+        bbox
+    }
+
+    fn center(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        let (px, py) = ((x - self.x).abs(), (y - self.y).abs());
+        let (a, b) = (self.rx, self.ry);
+
+        // find the closest point on the ellipse by iteratively approximating
+        // it with the circle of curvature, see
+        // https://github.com/0xfaded/ellipse_demo/issues/1
+        let (mut tx, mut ty) = (FRAC_1_SQRT_2, FRAC_1_SQRT_2);
+        for _ in 0..3 {
+            let (ex, ey) = (
+                (a * a - b * b) * tx.powi(3) / a,
+                (b * b - a * a) * ty.powi(3) / b,
+            );
+
+            let (rx, ry) = (a * tx - ex, b * ty - ey);
+            let (qx, qy) = (px - ex, py - ey);
+            let r = rx.hypot(ry);
+            let q = qx.hypot(qy).max(f32::EPSILON);
+
+            tx = ((qx * r / q + ex) / a).clamp(0.0, 1.0);
+            ty = ((qy * r / q + ey) / b).clamp(0.0, 1.0);
+            let t = tx.hypot(ty);
+            tx /= t;
+            ty /= t;
         }
 
-        d
+        let d = (px - a * tx).hypot(py - b * ty);
+        if (px / a).powi(2) + (py / b).powi(2) < 1.0 {
+            -d
+        } else {
+            d
+        }
     }
 
     fn area(&self) -> f32 {
-        let mut area = 0.0;
+        PI * self.rx * self.ry
+    }
 
-        for i in 0..self.points.len() {
-            let j = (i + 1) % self.points.len();
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        let a = rng.gen_range(0.0..2.0 * PI);
+        let d = rng.gen::<f32>().sqrt();
 
-            let (x0, y0) = self.points[i];
-            let (x1, y1) = self.points[j];
-            area += x0 * y1 - x1 * y0;
-        }
+        (
+            self.x + a.cos() * d * self.rx,
+            self.y + a.sin() * d * self.ry,
+        )
+    }
 
-        area = area.abs() / 2.0;
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        writeln!(
+            w,
+            r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" fill="{}" stroke="{}"/>"#,
+            self.x, self.y, self.rx, self.ry, fill, stroke
+        )
+    }
 
-        area - self.holes.iter().map(|h| h.area()).sum::<f32>()
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        pdf::write_ellipse(w, self.x, self.y, self.rx, self.ry)
+    }
+
+    fn strokes(&self, tolerance: f32) -> Vec<Stroke> {
+        vec![plot::polygon_stroke(&self.outline(tolerance))]
+    }
+}
+
+impl Packable for Ellipse {
+    fn placed(&self, x: f32, y: f32, scale: f32) -> Self {
+        let s = scale / self.rx.min(self.ry);
+        Ellipse::new(x, y, self.rx * s, self.ry * s)
+    }
+
+    fn scale(&self) -> f32 {
+        self.rx.min(self.ry)
+    }
+
+    fn outer_ratio(&self) -> f32 {
+        self.rx.max(self.ry) / self.rx.min(self.ry)
+    }
+
+    fn outline(&self, tolerance: f32) -> Vec<(f32, f32)> {
+        let n = ((2.0 * PI * self.rx.max(self.ry) / tolerance).ceil() as usize).max(3);
+        (0..n)
+            .map(|i| self.point_at(2.0 * PI * i as f32 / n as f32))
+            .collect()
+    }
+}
+
+impl RoundedRect {
+    /// Create a rect with the given bbox, the radius is clamped so that it's at
+    /// most half of the shortest side.
+    pub fn new(bbox: Bbox, radius: f32) -> Self {
+        let radius = radius.clamp(0.0, bbox.width().min(bbox.height()) / 2.0);
+        Self { bbox, radius }
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+impl Shape for RoundedRect {
+    fn bbox(&self) -> Bbox {
+        self.bbox.clone()
+    }
+
+    fn center(&self) -> (f32, f32) {
+        self.bbox.center()
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        let (cx, cy) = self.center();
+        let dx = (x - cx).abs() - self.bbox.width() / 2.0 + self.radius;
+        let dy = (y - cy).abs() - self.bbox.height() / 2.0 + self.radius;
+
+        let out = f32::max(dx, 0.0).hypot(f32::max(dy, 0.0));
+        let ins = f32::max(dx, dy).min(0.0);
+
+        out + ins - self.radius
+    }
+
+    fn area(&self) -> f32 {
+        self.bbox.area() - (4.0 - PI) * self.radius.powi(2)
     }
 
     fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
@@ -284,10 +639,866 @@ impl Shape for Polyline {
     fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
         writeln!(
             w,
-            r#"<path d="{}" fill="{}" stroke="{}"/>"#,
-            self.get_d(),
+            r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="{}" stroke="{}"/>"#,
+            self.bbox.x0(),
+            self.bbox.y0(),
+            self.bbox.width(),
+            self.bbox.height(),
+            self.radius,
             fill,
             stroke
         )
     }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let r = self.radius;
+        let (x0, y0) = (self.bbox.x0(), self.bbox.y0());
+        let (x1, y1) = (self.bbox.x1(), self.bbox.y1());
+
+        writeln!(w, "{} {} m", x0 + r, y0)?;
+        for (i, &(x, y, cx, cy)) in [
+            (x1 - r, y0, x1 - r, y0 + r),
+            (x1, y1 - r, x1 - r, y1 - r),
+            (x0 + r, y1, x0 + r, y1 - r),
+            (x0, y0 + r, x0 + r, y0 + r),
+        ]
+        .iter()
+        .enumerate()
+        {
+            // each corner starts a quarter turn after the previous one
+            let a0 = FRAC_PI_2 * (i as f32 - 1.0);
+            writeln!(w, "{} {} l", x, y)?;
+            pdf::write_arc(w, (cx, cy), (r, r), a0, a0 + FRAC_PI_2)?;
+        }
+        writeln!(w, "h")
+    }
+}
+
+impl Star {
+    pub fn new(x: f32, y: f32, outer: f32, inner: f32, points: u32) -> Self {
+        assert!(points >= 2);
+
+        Self {
+            x,
+            y,
+            outer,
+            inner,
+            points,
+            rotation: 0.0,
+        }
+    }
+
+    /// Vertices of the star alternating between tips and inner vertices,
+    /// starting from the tip at angle `rotation`.
+    pub fn vertices(&self) -> Vec<(f32, f32)> {
+        let an = PI / self.points as f32;
+        (0..2 * self.points)
+            .map(|i| {
+                let r = if i % 2 == 0 { self.outer } else { self.inner };
+                let a = self.rotation + an * i as f32;
+                (self.x + a.cos() * r, self.y + a.sin() * r)
+            })
+            .collect()
+    }
+}
+
+impl Shape for Star {
+    fn bbox(&self) -> Bbox {
+        let vertices = self.vertices();
+        let mut bbox = Bbox::new(vertices[0].0, vertices[0].1);
+        for &(x, y) in &vertices[1..] {
+            bbox.expand(x, y);
+        }
+        bbox
+    }
+
+    fn center(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        // the star is symmetric across the lines through its center and its
+        // vertices, fold the point in the wedge between the first tip and the
+        // next inner vertex where the closest edge is the one joining them
+        let an = PI / self.points as f32;
+        let (dx, dy) = (x - self.x, y - self.y);
+        let mut a = (dy.atan2(dx) - self.rotation).rem_euclid(2.0 * an);
+        if a > an {
+            a = 2.0 * an - a;
+        }
+        let r = dx.hypot(dy);
+        let (qx, qy) = (r * a.cos(), r * a.sin());
+
+        let (ex, ey) = (self.inner * an.cos() - self.outer, self.inner * an.sin());
+        let (wx, wy) = (qx - self.outer, qy);
+        let t = ((wx * ex + wy * ey) / (ex * ex + ey * ey)).clamp(0.0, 1.0);
+        let d = (wx - ex * t).hypot(wy - ey * t);
+
+        // the center is on the left of the edge
+        if ex * wy - ey * wx > 0.0 {
+            -d
+        } else {
+            d
+        }
+    }
+
+    fn area(&self) -> f32 {
+        self.points as f32 * self.outer * self.inner * (PI / self.points as f32).sin()
+    }
+
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        // all the triangles between the center and an edge have the same area
+        let vertices = self.vertices();
+        let i = rng.gen_range(0..vertices.len());
+
+        random_point_in_triangle(
+            rng,
+            (self.x, self.y),
+            vertices[i],
+            vertices[(i + 1) % vertices.len()],
+        )
+    }
+
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        let points = self
+            .vertices()
+            .iter()
+            .map(|(x, y)| format!("{},{}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(
+            w,
+            r#"<polygon points="{}" fill="{}" stroke="{}"/>"#,
+            points, fill, stroke
+        )
+    }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        pdf::write_polygon(w, &self.vertices())
+    }
+
+    fn strokes(&self, _tolerance: f32) -> Vec<Stroke> {
+        vec![plot::polygon_stroke(&self.vertices())]
+    }
+}
+
+impl Capsule {
+    pub fn new(a: (f32, f32), b: (f32, f32), radius: f32) -> Self {
+        Self { a, b, radius }
+    }
+
+    fn length(&self) -> f32 {
+        (self.b.0 - self.a.0).hypot(self.b.1 - self.a.1)
+    }
+
+    /// Unit vector going from a to b, or the x axis if a and b coincide.
+    fn direction(&self) -> (f32, f32) {
+        let l = self.length();
+        if l > 0.0 {
+            ((self.b.0 - self.a.0) / l, (self.b.1 - self.a.1) / l)
+        } else {
+            (1.0, 0.0)
+        }
+    }
+}
+
+impl Shape for Capsule {
+    fn bbox(&self) -> Bbox {
+        let mut bbox = Bbox::new(self.a.0 - self.radius, self.a.1 - self.radius);
+        bbox.expand(self.a.0 + self.radius, self.a.1 + self.radius);
+        bbox.expand(self.b.0 - self.radius, self.b.1 - self.radius);
+        bbox.expand(self.b.0 + self.radius, self.b.1 + self.radius);
+        bbox
+    }
+
+    fn center(&self) -> (f32, f32) {
+        ((self.a.0 + self.b.0) / 2.0, (self.a.1 + self.b.1) / 2.0)
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        let (ex, ey) = (self.b.0 - self.a.0, self.b.1 - self.a.1);
+        let (wx, wy) = (x - self.a.0, y - self.a.1);
+
+        let l2 = ex * ex + ey * ey;
+        let t = if l2 > 0.0 {
+            ((wx * ex + wy * ey) / l2).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        (wx - ex * t).hypot(wy - ey * t) - self.radius
+    }
+
+    fn area(&self) -> f32 {
+        2.0 * self.radius * self.length() + PI * self.radius.powi(2)
+    }
+
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        // without a radius there's no area to pick a point from
+        if self.radius <= 0.0 {
+            return self.center();
+        }
+
+        let (dx, dy) = self.direction();
+        let rect_area = 2.0 * self.radius * self.length();
+
+        if rng.gen_range(0.0..self.area()) < rect_area {
+            let t = rng.gen_range(0.0..=self.length());
+            let s = rng.gen_range(-self.radius..=self.radius);
+            return (self.a.0 + dx * t - dy * s, self.a.1 + dy * t + dx * s);
+        }
+
+        // the two caps make up a whole circle, the half behind a goes around
+        // a and the half in front of b around b
+        let (x, y) = Circle::new(0.0, 0.0, self.radius).random_point(rng);
+        if x * dx + y * dy < 0.0 {
+            (self.a.0 + x, self.a.1 + y)
+        } else {
+            (self.b.0 + x, self.b.1 + y)
+        }
+    }
+
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        let (dx, dy) = self.direction();
+        let (nx, ny) = (-dy * self.radius, dx * self.radius);
+        let r = self.radius;
+
+        writeln!(
+            w,
+            r#"<path d="M {},{} L {},{} A {} {} 0 0 0 {},{} L {},{} A {} {} 0 0 0 {},{} Z" fill="{}" stroke="{}"/>"#,
+            self.a.0 + nx,
+            self.a.1 + ny,
+            self.b.0 + nx,
+            self.b.1 + ny,
+            r,
+            r,
+            self.b.0 - nx,
+            self.b.1 - ny,
+            self.a.0 - nx,
+            self.a.1 - ny,
+            r,
+            r,
+            self.a.0 + nx,
+            self.a.1 + ny,
+            fill,
+            stroke
+        )
+    }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let (dx, dy) = self.direction();
+        let (nx, ny) = (-dy * self.radius, dx * self.radius);
+        let r = self.radius;
+
+        // the normal is a quarter turn after the direction, the caps go from
+        // one side to the other around the ends
+        let a = dx.atan2(-dy);
+
+        writeln!(w, "{} {} m", self.a.0 + nx, self.a.1 + ny)?;
+        writeln!(w, "{} {} l", self.b.0 + nx, self.b.1 + ny)?;
+        pdf::write_arc(w, self.b, (r, r), a, a - PI)?;
+        writeln!(w, "{} {} l", self.a.0 - nx, self.a.1 - ny)?;
+        pdf::write_arc(w, self.a, (r, r), a - PI, a - 2.0 * PI)?;
+        writeln!(w, "h")
+    }
+}
+
+impl Annulus {
+    pub fn new(x: f32, y: f32, inner: f32, outer: f32) -> Self {
+        assert!(inner <= outer);
+        Self { x, y, inner, outer }
+    }
+}
+
+impl Shape for Annulus {
+    fn bbox(&self) -> Bbox {
+        Circle::new(self.x, self.y, self.outer).bbox()
+    }
+
+    fn center(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        let d = (x - self.x).hypot(y - self.y);
+        (d - (self.outer + self.inner) / 2.0).abs() - (self.outer - self.inner) / 2.0
+    }
+
+    fn area(&self) -> f32 {
+        PI * (self.outer.powi(2) - self.inner.powi(2))
+    }
+
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        let a = rng.gen_range(0.0..2.0 * PI);
+        let r2 = rng.gen_range(self.inner.powi(2)..=self.outer.powi(2));
+        let d = r2.sqrt();
+
+        (self.x + a.cos() * d, self.y + a.sin() * d)
+    }
+
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        let circle = |r: f32| {
+            format!(
+                "M {},{} A {} {} 0 1 0 {},{} A {} {} 0 1 0 {},{} Z",
+                self.x - r,
+                self.y,
+                r,
+                r,
+                self.x + r,
+                self.y,
+                r,
+                r,
+                self.x - r,
+                self.y
+            )
+        };
+
+        writeln!(
+            w,
+            r#"<path d="{} {}" fill="{}" stroke="{}" fill-rule="evenodd"/>"#,
+            circle(self.outer),
+            circle(self.inner),
+            fill,
+            stroke
+        )
+    }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        pdf::write_ellipse(w, self.x, self.y, self.outer, self.outer)?;
+        pdf::write_ellipse(w, self.x, self.y, self.inner, self.inner)
+    }
+
+    fn strokes(&self, _tolerance: f32) -> Vec<Stroke> {
+        [self.outer, self.inner]
+            .iter()
+            .map(|&radius| Stroke::Circle {
+                x: self.x,
+                y: self.y,
+                radius,
+            })
+            .collect()
+    }
+}
+
+impl Pie {
+    /// Create a pie going from `start` to `end` counterclockwise, in radians.
+    /// The sweep of the pie is at most a whole turn.
+    pub fn new(x: f32, y: f32, radius: f32, start: f32, end: f32) -> Self {
+        assert!(start <= end && end - start <= 2.0 * PI);
+
+        Self {
+            x,
+            y,
+            radius,
+            start,
+            end,
+        }
+    }
+
+    fn point_at(&self, a: f32) -> (f32, f32) {
+        (
+            self.x + a.cos() * self.radius,
+            self.y + a.sin() * self.radius,
+        )
+    }
+}
+
+impl Shape for Pie {
+    fn bbox(&self) -> Bbox {
+        let mut bbox = Bbox::new(self.x, self.y);
+        for a in [self.start, self.end] {
+            let (x, y) = self.point_at(a);
+            bbox.expand(x, y);
+        }
+
+        // the extremes of the arc are on the axes, if they're part of it
+        let first = (self.start / FRAC_PI_2).ceil() as i32;
+        let last = (self.end / FRAC_PI_2).floor() as i32;
+        for i in first..=last.min(first + 3) {
+            let (x, y) = self.point_at(i as f32 * FRAC_PI_2);
+            bbox.expand(x, y);
+        }
+
+        bbox
+    }
+
+    fn center(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        // rotate the point so that the bisector of the pie is the y axis and
+        // exploit the symmetry, see https://iquilezles.org/articles/distfunctions2d/
+        let half = (self.end - self.start) / 2.0;
+        let (s, c) = (FRAC_PI_2 - (self.start + half)).sin_cos();
+        let (px, py) = (x - self.x, y - self.y);
+        let (px, py) = ((px * c - py * s).abs(), px * s + py * c);
+
+        let (sx, sy) = half.sin_cos();
+        let l = px.hypot(py) - self.radius;
+        let t = (px * sx + py * sy).clamp(0.0, self.radius);
+        let m = (px - sx * t).hypot(py - sy * t);
+
+        l.max(m * (sy * px - sx * py).signum())
+    }
+
+    fn area(&self) -> f32 {
+        (self.end - self.start) * self.radius.powi(2) / 2.0
+    }
+
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        let a = rng.gen_range(self.start..=self.end);
+        let d = self.radius * rng.gen::<f32>().sqrt();
+
+        (self.x + a.cos() * d, self.y + a.sin() * d)
+    }
+
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        if self.end - self.start >= 2.0 * PI {
+            return Circle::new(self.x, self.y, self.radius).write_svg(w, fill, stroke);
+        }
+
+        let (x0, y0) = self.point_at(self.start);
+        let (x1, y1) = self.point_at(self.end);
+        let large_arc = i32::from(self.end - self.start > PI);
+
+        writeln!(
+            w,
+            r#"<path d="M {},{} L {},{} A {} {} 0 {} 1 {},{} Z" fill="{}" stroke="{}"/>"#,
+            self.x, self.y, x0, y0, self.radius, self.radius, large_arc, x1, y1, fill, stroke
+        )
+    }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        if self.end - self.start >= 2.0 * PI {
+            return Circle::new(self.x, self.y, self.radius).write_pdf(w);
+        }
+
+        let (x0, y0) = self.point_at(self.start);
+        writeln!(w, "{} {} m", self.x, self.y)?;
+        writeln!(w, "{} {} l", x0, y0)?;
+        pdf::write_arc(
+            w,
+            (self.x, self.y),
+            (self.radius, self.radius),
+            self.start,
+            self.end,
+        )?;
+        writeln!(w, "h")
+    }
+}
+
+impl Superellipse {
+    pub fn new(x: f32, y: f32, rx: f32, ry: f32, n: f32) -> Self {
+        assert!(n > 0.0);
+        Self { x, y, rx, ry, n }
+    }
+
+    /// Point on the first quadrant of the curve, relative to the center, at
+    /// the given parameter in [0, PI/2].
+    fn quadrant_point(&self, t: f32) -> (f32, f32) {
+        let e = 2.0 / self.n;
+        let (c, s) = (t.cos().max(0.0), t.sin().max(0.0));
+        (self.rx * c.powf(e), self.ry * s.powf(e))
+    }
+
+    /// Points along the curve.
+    fn outline(&self) -> Vec<(f32, f32)> {
+        const SAMPLES: usize = 64;
+
+        let mut points = Vec::with_capacity(SAMPLES * 4);
+        for (sx, sy) in [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)] {
+            for i in 0..SAMPLES {
+                let mut t = FRAC_PI_2 * i as f32 / SAMPLES as f32;
+                if sx * sy < 0.0 {
+                    t = FRAC_PI_2 - t;
+                }
+
+                let (x, y) = self.quadrant_point(t);
+                points.push((self.x + sx * x, self.y + sy * y));
+            }
+        }
+        points
+    }
+}
+
+impl Shape for Superellipse {
+    fn bbox(&self) -> Bbox {
+        Ellipse::new(self.x, self.y, self.rx, self.ry).bbox()
+    }
+
+    fn center(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        const SAMPLES: usize = 32;
+        const REFINEMENTS: usize = 16;
+
+        // there's no closed form for the distance, search the closest point on
+        // the first quadrant of the curve numerically
+        let (px, py) = ((x - self.x).abs(), (y - self.y).abs());
+        let dist = |t: f32| {
+            let (cx, cy) = self.quadrant_point(t);
+            (px - cx).hypot(py - cy)
+        };
+
+        let step = FRAC_PI_2 / SAMPLES as f32;
+        let best = (0..=SAMPLES)
+            .map(|i| i as f32 * step)
+            .min_by(|&a, &b| dist(a).total_cmp(&dist(b)))
+            .unwrap();
+
+        let (mut lo, mut hi) = ((best - step).max(0.0), (best + step).min(FRAC_PI_2));
+        for _ in 0..REFINEMENTS {
+            let m0 = lo + (hi - lo) / 3.0;
+            let m1 = hi - (hi - lo) / 3.0;
+            if dist(m0) < dist(m1) {
+                hi = m1;
+            } else {
+                lo = m0;
+            }
+        }
+
+        let d = dist((lo + hi) / 2.0);
+        if (px / self.rx).powf(self.n) + (py / self.ry).powf(self.n) < 1.0 {
+            -d
+        } else {
+            d
+        }
+    }
+
+    fn area(&self) -> f32 {
+        let n = f64::from(self.n);
+        let a = 4.0 * gamma(1.0 + 1.0 / n).powi(2) / gamma(1.0 + 2.0 / n);
+        a as f32 * self.rx * self.ry
+    }
+
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        let bbox = self.bbox();
+        loop {
+            let (x, y) = bbox.random_point(rng);
+            let (dx, dy) = ((x - self.x) / self.rx, (y - self.y) / self.ry);
+            if dx.abs().powf(self.n) + dy.abs().powf(self.n) <= 1.0 {
+                break (x, y);
+            }
+        }
+    }
+
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        let points = self
+            .outline()
+            .iter()
+            .map(|(x, y)| format!("{},{}", x, y))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(
+            w,
+            r#"<polygon points="{}" fill="{}" stroke="{}"/>"#,
+            points, fill, stroke
+        )
+    }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        pdf::write_polygon(w, &self.outline())
+    }
+
+    fn strokes(&self, _tolerance: f32) -> Vec<Stroke> {
+        vec![plot::polygon_stroke(&self.outline())]
+    }
+}
+
+impl Polyline {
+    pub fn new(points: Vec<(f32, f32)>) -> Option<Self> {
+        if points.is_empty() {
+            None
+        } else {
+            let (mut cx, mut cy) = points[0];
+            let mut bbox = Bbox::new(cx, cy);
+            for &(x, y) in points.iter().skip(1) {
+                bbox.expand(x, y);
+                cx += x;
+                cy += y;
+            }
+            cx /= points.len() as f32;
+            cy /= points.len() as f32;
+            Some(Self {
+                points,
+                cx,
+                cy,
+                bbox,
+                holes: vec![],
+            })
+        }
+    }
+
+    /// Points of the outer boundary.
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    pub fn holes(&self) -> &[Polyline] {
+        &self.holes
+    }
+
+    /// Cut the given hole out of this polyline.
+    ///
+    /// The hole is clipped against the boundary and merged with the holes
+    /// that it overlaps so that `area()` stays exact. Return false and leave
+    /// the polyline untouched if the hole doesn't overlap it or if cutting it
+    /// out would split the polyline in more than one piece, use `difference`
+    /// in that case.
+    pub fn push_hole(&mut self, hole: Polyline) -> bool {
+        // with concave boundaries all the vertices can be inside while some
+        // edges are not
+        let completely_contained = hole.points.iter().all(|&(x, y)| self.sdf(x, y) < 0.0)
+            && !edges_cross(&self.points, &hole.points)
+            && self.holes.iter().all(|h| !h.bbox.overlaps(&hole.bbox));
+        if completely_contained {
+            self.holes.push(hole);
+            return true;
+        }
+
+        if self.intersection(&hole).is_empty() {
+            return false;
+        }
+
+        let mut pieces = self.difference(&hole);
+        if pieces.len() != 1 {
+            return false;
+        }
+
+        *self = pieces.pop().unwrap();
+        true
+    }
+
+    /// Parts of the plane covered by both polylines.
+    pub fn intersection(&self, other: &Polyline) -> Vec<Polyline> {
+        self.overlay(other, OverlayRule::Intersect)
+    }
+
+    /// Parts of the plane covered by this polyline but not by the other.
+    pub fn difference(&self, other: &Polyline) -> Vec<Polyline> {
+        self.overlay(other, OverlayRule::Difference)
+    }
+
+    /// Parts of the plane covered by any of the polylines.
+    pub fn union(&self, other: &Polyline) -> Vec<Polyline> {
+        self.overlay(other, OverlayRule::Union)
+    }
+
+    fn overlay(&self, other: &Polyline, rule: OverlayRule) -> Vec<Polyline> {
+        self.contours()
+            .overlay(&other.contours(), rule, FillRule::EvenOdd)
+            .into_iter()
+            .filter_map(|shape| {
+                let mut contours = shape
+                    .into_iter()
+                    .map(|c| c.into_iter().map(|[x, y]| (x, y)).collect::<Vec<_>>());
+
+                let mut polyline = Polyline::new(contours.next()?)?;
+                polyline.holes = contours.filter_map(Polyline::new).collect();
+                Some(polyline)
+            })
+            .collect()
+    }
+
+    /// Boundary and holes in the format expected by `i_overlay`.
+    fn contours(&self) -> Vec<Vec<[f32; 2]>> {
+        let mut contours = vec![self.points.iter().map(|&(x, y)| [x, y]).collect()];
+        for hole in &self.holes {
+            contours.extend(hole.contours());
+        }
+        contours
+    }
+
+    fn get_d(&self) -> String {
+        let mut d = format!("M {},{}", self.points[0].0, self.points[0].1);
+        for &(x, y) in self.points.iter().skip(1) {
+            d += &format!("L {},{}", x, y);
+        }
+        d += "Z";
+
+        for hole in &self.holes {
+            d += &hole.get_d();
+        }
+
+        d
+    }
+}
+
+impl Shape for Polyline {
+    fn bbox(&self) -> Bbox {
+        self.bbox.clone()
+    }
+
+    fn center(&self) -> (f32, f32) {
+        (self.cx, self.cy)
+    }
+
+    fn sdf(&self, x: f32, y: f32) -> f32 {
+        let mut d = polygon_sdf(&self.points, x, y);
+        for h in &self.holes {
+            let dd = h.sdf(x, y);
+            d = d.max(-dd);
+        }
+
+        d
+    }
+
+    fn area(&self) -> f32 {
+        let mut area = 0.0;
+
+        for i in 0..self.points.len() {
+            let j = (i + 1) % self.points.len();
+
+            let (x0, y0) = self.points[i];
+            let (x1, y1) = self.points[j];
+            area += x0 * y1 - x1 * y0;
+        }
+
+        area = area.abs() / 2.0;
+
+        area - self.holes.iter().map(|h| h.area()).sum::<f32>()
+    }
+
+    fn random_point<R: Rng>(&self, rng: &mut R) -> (f32, f32) {
+        loop {
+            let (x, y) = self.bbox.random_point(rng);
+            if self.sdf(x, y) <= 0.0 {
+                break (x, y);
+            }
+        }
+    }
+
+    fn write_svg<W: Write>(&self, w: &mut W, fill: &str, stroke: &str) -> io::Result<()> {
+        writeln!(
+            w,
+            r#"<path d="{}" fill="{}" stroke="{}"/>"#,
+            self.get_d(),
+            fill,
+            stroke
+        )
+    }
+
+    fn write_pdf<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        pdf::write_polygon(w, &self.points)?;
+        for hole in &self.holes {
+            hole.write_pdf(w)?;
+        }
+        Ok(())
+    }
+
+    fn strokes(&self, _tolerance: f32) -> Vec<Stroke> {
+        let mut strokes = vec![plot::polygon_stroke(&self.points)];
+        for hole in &self.holes {
+            strokes.push(plot::polygon_stroke(&hole.points));
+        }
+        strokes
+    }
+}
+
+/// Signed distance between the given point and the closed polygon with the
+/// given vertices.
+fn polygon_sdf(points: &[(f32, f32)], x: f32, y: f32) -> f32 {
+    let (x0, y0) = points[0];
+    let mut d = (x - x0).powi(2) + (y - y0).powi(2);
+    let mut s = 1.0;
+
+    for i in 0..points.len() {
+        let j = (i + points.len() - 1) % points.len();
+
+        let (ix, iy) = points[i];
+        let (jx, jy) = points[j];
+        let (ex, ey) = (jx - ix, jy - iy);
+        let (wx, wy) = (x - ix, y - iy);
+
+        let t = ((wx * ex + wy * ey) / (ex.powi(2) + ey.powi(2))).clamp(0.0, 1.0);
+
+        let (bx, by) = (wx - ex * t, wy - ey * t);
+
+        d = d.min(bx.powi(2) + by.powi(2));
+
+        let a = y >= iy;
+        let b = y < jy;
+        let c = ex * wy > ey * wx;
+        if (a && b && c) || (!a && !b && !c) {
+            s *= -1.0;
+        }
+    }
+
+    s * d.sqrt()
+}
+
+/// Whether any edge of the closed polygon `a` touches or crosses any edge of
+/// the closed polygon `b`.
+fn edges_cross(a: &[(f32, f32)], b: &[(f32, f32)]) -> bool {
+    fn edges(p: &[(f32, f32)]) -> impl Iterator<Item = ((f32, f32), (f32, f32))> + '_ {
+        (0..p.len()).map(move |i| (p[i], p[(i + 1) % p.len()]))
+    }
+    let orientation = |(ax, ay): (f32, f32), (bx, by): (f32, f32), (cx, cy): (f32, f32)| {
+        (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+    };
+
+    edges(a).any(|(p0, p1)| {
+        edges(b).any(|(q0, q1)| {
+            let disjoint = p0.0.max(p1.0) < q0.0.min(q1.0)
+                || q0.0.max(q1.0) < p0.0.min(p1.0)
+                || p0.1.max(p1.1) < q0.1.min(q1.1)
+                || q0.1.max(q1.1) < p0.1.min(p1.1);
+
+            // collinear edges with overlapping bboxes count as touching
+            !disjoint
+                && orientation(p0, p1, q0) * orientation(p0, p1, q1) <= 0.0
+                && orientation(q0, q1, p0) * orientation(q0, q1, p1) <= 0.0
+        })
+    })
+}
+
+/// Uniformly sample a point in the triangle with the given vertices.
+fn random_point_in_triangle<R: Rng>(
+    rng: &mut R,
+    (ax, ay): (f32, f32),
+    (bx, by): (f32, f32),
+    (cx, cy): (f32, f32),
+) -> (f32, f32) {
+    let (mut u, mut v) = (rng.gen::<f32>(), rng.gen::<f32>());
+    if u + v > 1.0 {
+        u = 1.0 - u;
+        v = 1.0 - v;
+    }
+
+    (
+        ax + u * (bx - ax) + v * (cx - ax),
+        ay + u * (by - ay) + v * (cy - ay),
+    )
+}
+
+/// Lanczos approximation of the gamma function for positive arguments.
+fn gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |acc, (i, c)| {
+            acc + c / (x + i as f64 + 1.0)
+        });
+
+    (2.0 * std::f64::consts::PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * sum
 }
@@ -0,0 +1,162 @@
+//! Criteria deciding when a packing is done, see `Settings::stop`.
+//!
+//! On the command line and in `Display` criteria are written as
+//! `<name>:<value>`, for example `placed:500` or `time:10`, and combined with
+//! `any(...)` and `all(...)`, for example `any(placed:500,time:10)`.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::progress::Progress;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopCriterion {
+    /// Stop once this many shapes have been placed.
+    Placed(usize),
+
+    /// Stop once this fraction of the area of the root is covered.
+    Coverage(f32),
+
+    /// Stop after this many attempts, placed or not.
+    Attempts(usize),
+
+    /// Stop after this many attempts in a row without placing any shape.
+    Stall(usize),
+
+    /// Stop after this many seconds.
+    Time(f32),
+
+    /// Stop once the fraction of attempts that place a shape falls below this
+    /// threshold, see `Progress::acceptance_rate`.
+    AcceptanceRate(f32),
+
+    /// Stop as soon as any of the criteria is met.
+    Any(Vec<StopCriterion>),
+
+    /// Stop once all the criteria are met at the same time.
+    All(Vec<StopCriterion>),
+}
+
+impl StopCriterion {
+    pub fn is_met(&self, progress: &Progress) -> bool {
+        match self {
+            StopCriterion::Placed(n) => progress.placed >= *n,
+            StopCriterion::Coverage(c) => progress.coverage >= *c,
+            StopCriterion::Attempts(n) => progress.attempts >= *n,
+            StopCriterion::Stall(n) => progress.stall >= *n,
+            StopCriterion::Time(t) => progress.elapsed.as_secs_f32() >= *t,
+            StopCriterion::AcceptanceRate(r) => progress.acceptance_rate < *r,
+            StopCriterion::Any(cs) => cs.iter().any(|c| c.is_met(progress)),
+            StopCriterion::All(cs) => cs.iter().all(|c| c.is_met(progress)),
+        }
+    }
+}
+
+impl fmt::Display for StopCriterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, name, cs: &[StopCriterion]| {
+            write!(f, "{}(", name)?;
+            for (i, c) in cs.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", c)?;
+            }
+            write!(f, ")")
+        };
+
+        match self {
+            StopCriterion::Placed(n) => write!(f, "placed:{}", n),
+            StopCriterion::Coverage(c) => write!(f, "coverage:{}", c),
+            StopCriterion::Attempts(n) => write!(f, "attempts:{}", n),
+            StopCriterion::Stall(n) => write!(f, "stall:{}", n),
+            StopCriterion::Time(t) => write!(f, "time:{}", t),
+            StopCriterion::AcceptanceRate(r) => write!(f, "acceptance_rate:{}", r),
+            StopCriterion::Any(cs) => list(f, "any", cs),
+            StopCriterion::All(cs) => list(f, "all", cs),
+        }
+    }
+}
+
+impl FromStr for StopCriterion {
+    type Err = String;
+
+    /// Parse the syntax written by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.contains(['(', ')']) {
+            let (name, args) = s
+                .strip_suffix(')')
+                .and_then(|s| s.split_once('('))
+                .ok_or_else(|| format!("unbalanced parentheses in {}", s))?;
+
+            let cs: Vec<_> = split_args(args)?
+                .into_iter()
+                .map(str::parse)
+                .collect::<Result<_, _>>()?;
+            // `any()` would never be met and `all()` would be met at once.
+            if cs.is_empty() {
+                return Err(format!("no criteria in {}", s));
+            }
+
+            return match name.trim() {
+                "any" => Ok(StopCriterion::Any(cs)),
+                "all" => Ok(StopCriterion::All(cs)),
+                _ => Err(format!("unknown combinator {}, use any or all", name)),
+            };
+        }
+
+        let (name, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("missing value in {}", s))?;
+        let invalid = || format!("invalid {} {}", name, value);
+
+        match name {
+            "placed" => value.parse().map(StopCriterion::Placed).map_err(|_| invalid()),
+            "coverage" => value.parse().map(StopCriterion::Coverage).map_err(|_| invalid()),
+            "attempts" => value.parse().map(StopCriterion::Attempts).map_err(|_| invalid()),
+            "stall" => value.parse().map(StopCriterion::Stall).map_err(|_| invalid()),
+            "time" => value.parse().map(StopCriterion::Time).map_err(|_| invalid()),
+            "acceptance_rate" => value
+                .parse()
+                .map(StopCriterion::AcceptanceRate)
+                .map_err(|_| invalid()),
+            _ => Err(format!(
+                "unknown criterion {}, use placed, coverage, attempts, stall, time or acceptance_rate",
+                name
+            )),
+        }
+    }
+}
+
+/// Split the arguments of a combinator at the commas that are not nested in
+/// other combinators.
+fn split_args(args: &str) -> Result<Vec<&str>, String> {
+    let mut parts = vec![];
+    let (mut depth, mut start) = (0, 0);
+
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Err(format!("unbalanced parentheses in {}", args)),
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&args[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if depth != 0 {
+        return Err(format!("unbalanced parentheses in {}", args));
+    }
+    if !args.trim().is_empty() {
+        parts.push(&args[start..]);
+    }
+
+    Ok(parts)
+}
@@ -0,0 +1,355 @@
+//! Import containers from the shapes of an SVG document.
+//!
+//! `<path>`, `<polygon>`, `<polyline>`, `<rect>`, `<circle>` and `<ellipse>`
+//! elements are supported, their `transform` and the ones of their ancestors
+//! are applied and their `fill-rule` decides which subpaths are holes.
+//! Elements that are not rendered directly, like the ones in `<defs>`, are
+//! ignored.
+
+use std::{error::Error, f32::consts::PI, fmt, fs, io, path::Path};
+
+pub use crate::nesting::FillRule;
+use crate::{
+    nesting,
+    path::{self, ParseError},
+    Polyline,
+};
+
+#[derive(Debug)]
+pub enum SvgError {
+    Io(io::Error),
+    Xml(roxmltree::Error),
+
+    /// The data of a path is not valid.
+    Path(ParseError),
+
+    /// An attribute required to describe a shape is missing or malformed.
+    InvalidAttribute {
+        element: String,
+        attribute: &'static str,
+    },
+
+    /// The document wasn't written by `dump_svg`, see `metadata`.
+    MissingMetadata,
+}
+
+/// Affine transformation `[a, b, c, d, e, f]` with the same meaning as the
+/// SVG `matrix` transform.
+type Transform = [f32; 6];
+
+type Subpaths = Vec<Vec<(f32, f32)>>;
+
+const IDENTITY: Transform = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// Elements whose children are not rendered directly.
+const NOT_RENDERED: &[&str] = &[
+    "defs", "clipPath", "mask", "marker", "pattern", "symbol", "metadata",
+];
+
+impl fmt::Display for SvgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SvgError::Io(e) => write!(f, "cannot read svg: {}", e),
+            SvgError::Xml(e) => write!(f, "invalid svg: {}", e),
+            SvgError::Path(e) => write!(f, "invalid path data: {}", e),
+            SvgError::InvalidAttribute { element, attribute } => {
+                write!(f, "invalid or missing {} in <{}>", attribute, element)
+            }
+            SvgError::MissingMetadata => write!(f, "no packing metadata found"),
+        }
+    }
+}
+
+impl Error for SvgError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SvgError::Io(e) => Some(e),
+            SvgError::Xml(e) => Some(e),
+            SvgError::Path(e) => Some(e),
+            SvgError::InvalidAttribute { .. } | SvgError::MissingMetadata => None,
+        }
+    }
+}
+
+impl From<io::Error> for SvgError {
+    fn from(e: io::Error) -> Self {
+        SvgError::Io(e)
+    }
+}
+
+impl From<roxmltree::Error> for SvgError {
+    fn from(e: roxmltree::Error) -> Self {
+        SvgError::Xml(e)
+    }
+}
+
+impl From<ParseError> for SvgError {
+    fn from(e: ParseError) -> Self {
+        SvgError::Path(e)
+    }
+}
+
+/// Read the SVG file at the given path, see `parse`.
+pub fn load(path: impl AsRef<Path>, tolerance: f32) -> Result<Vec<Polyline>, SvgError> {
+    let data = fs::read_to_string(path)?;
+    parse(&data, tolerance)
+}
+
+/// Extract the shapes of the given SVG document as polylines ready to be used
+/// as containers. Curves are flattened so that they're at most `tolerance`
+/// away from the real curve, in user units of the document.
+pub fn parse(data: &str, tolerance: f32) -> Result<Vec<Polyline>, SvgError> {
+    // the SVG 1.1 doctype is common, including in the output of `dump_svg`
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..roxmltree::ParsingOptions::default()
+    };
+    let doc = roxmltree::Document::parse_with_options(data, options)?;
+
+    let mut containers = vec![];
+    collect(doc.root_element(), IDENTITY, &mut containers, tolerance)?;
+    Ok(containers)
+}
+
+fn collect(
+    node: roxmltree::Node,
+    parent_transform: Transform,
+    containers: &mut Vec<Polyline>,
+    tolerance: f32,
+) -> Result<(), SvgError> {
+    let name = node.tag_name().name();
+    if NOT_RENDERED.contains(&name) {
+        return Ok(());
+    }
+
+    let transform = match node.attribute("transform") {
+        Some(t) => compose(
+            parent_transform,
+            parse_transform(t).ok_or_else(|| invalid(node, "transform"))?,
+        ),
+        None => parent_transform,
+    };
+
+    // keep the flattening error within tolerance after scaling
+    let scale = (transform[0] * transform[3] - transform[1] * transform[2])
+        .abs()
+        .sqrt();
+    let local_tolerance = if scale > 0.0 {
+        tolerance / scale
+    } else {
+        tolerance
+    };
+
+    if let Some(subpaths) = element_subpaths(node, local_tolerance)? {
+        let subpaths = subpaths
+            .into_iter()
+            .map(|path| path.into_iter().map(|p| apply(&transform, p)).collect())
+            .collect();
+
+        containers.extend(nesting::nest(subpaths, fill_rule(node)));
+    }
+
+    for child in node.children().filter(|c| c.is_element()) {
+        collect(child, transform, containers, tolerance)?;
+    }
+
+    Ok(())
+}
+
+/// Points of the subpaths of the given element in its own coordinate system,
+/// or `None` if the element is not a shape.
+fn element_subpaths(node: roxmltree::Node, tolerance: f32) -> Result<Option<Subpaths>, SvgError> {
+    let num = |attribute: &'static str| {
+        node.attribute(attribute)
+            .and_then(parse_length)
+            .ok_or_else(|| invalid(node, attribute))
+    };
+    let num_opt = |attribute: &'static str| {
+        node.attribute(attribute)
+            .map(|v| parse_length(v).ok_or_else(|| invalid(node, attribute)))
+            .transpose()
+    };
+    let num_or =
+        |attribute: &'static str, default: f32| num_opt(attribute).map(|v| v.unwrap_or(default));
+    let size = |attribute: &'static str, value: f32| {
+        if value.is_finite() && value >= 0.0 {
+            Ok(value)
+        } else {
+            Err(invalid(node, attribute))
+        }
+    };
+
+    let d = match node.tag_name().name() {
+        "path" => node.attribute("d").unwrap_or("").to_string(),
+        "polygon" | "polyline" => {
+            let numbers = parse_numbers(node.attribute("points").unwrap_or(""))
+                .ok_or_else(|| invalid(node, "points"))?;
+
+            let points = numbers
+                .chunks_exact(2)
+                .map(|c| (c[0], c[1]))
+                .collect::<Vec<_>>();
+
+            return Ok(Some(vec![points]));
+        }
+        "rect" => {
+            let (x, y) = (num_or("x", 0.0)?, num_or("y", 0.0)?);
+            let (w, h) = (
+                size("width", num("width")?)?,
+                size("height", num("height")?)?,
+            );
+
+            // like in browsers, an empty rect isn't drawn
+            if w == 0.0 || h == 0.0 {
+                return Ok(None);
+            }
+
+            // a missing radius defaults to the other one
+            let rx = num_opt("rx")?.map(|rx| size("rx", rx)).transpose()?;
+            let ry = num_opt("ry")?.map(|ry| size("ry", ry)).transpose()?;
+            let (rx, ry) = (rx.or(ry).unwrap_or(0.0), ry.or(rx).unwrap_or(0.0));
+            let (rx, ry) = (rx.min(w / 2.0), ry.min(h / 2.0));
+
+            format!(
+                "M {},{} H {} A {rx} {ry} 0 0 1 {},{} V {} A {rx} {ry} 0 0 1 {},{} H {} A {rx} {ry} 0 0 1 {},{} V {} A {rx} {ry} 0 0 1 {},{} Z",
+                x + rx,
+                y,
+                x + w - rx,
+                x + w,
+                y + ry,
+                y + h - ry,
+                x + w - rx,
+                y + h,
+                x + rx,
+                x,
+                y + h - ry,
+                y + ry,
+                x + rx,
+                y,
+                rx = rx,
+                ry = ry,
+            )
+        }
+        "circle" => {
+            let (cx, cy) = (num_or("cx", 0.0)?, num_or("cy", 0.0)?);
+            let r = size("r", num("r")?)?;
+            ellipse_d(cx, cy, r, r)
+        }
+        "ellipse" => {
+            let (cx, cy) = (num_or("cx", 0.0)?, num_or("cy", 0.0)?);
+            let (rx, ry) = (size("rx", num("rx")?)?, size("ry", num("ry")?)?);
+            ellipse_d(cx, cy, rx, ry)
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(path::parse_subpaths(&d, tolerance)?))
+}
+
+fn ellipse_d(cx: f32, cy: f32, rx: f32, ry: f32) -> String {
+    format!(
+        "M {},{} A {rx} {ry} 0 0 1 {},{} A {rx} {ry} 0 0 1 {},{} Z",
+        cx - rx,
+        cy,
+        cx + rx,
+        cy,
+        cx - rx,
+        cy,
+        rx = rx,
+        ry = ry,
+    )
+}
+
+/// Fill rule of the given element, which is inherited from its ancestors.
+fn fill_rule(node: roxmltree::Node) -> FillRule {
+    for n in node.ancestors() {
+        let style = n.attribute("style").and_then(|style| {
+            style.split(';').find_map(|decl| {
+                let (k, v) = decl.split_once(':')?;
+                (k.trim() == "fill-rule").then(|| v.trim())
+            })
+        });
+
+        match style.or_else(|| n.attribute("fill-rule")) {
+            Some("evenodd") => return FillRule::EvenOdd,
+            Some("nonzero") => return FillRule::NonZero,
+            _ => {}
+        }
+    }
+
+    FillRule::NonZero
+}
+
+fn invalid(node: roxmltree::Node, attribute: &'static str) -> SvgError {
+    SvgError::InvalidAttribute {
+        element: node.tag_name().name().to_string(),
+        attribute,
+    }
+}
+
+/// Parse a length in user units, `px` is the only unit supported.
+fn parse_length(s: &str) -> Option<f32> {
+    s.trim().trim_end_matches("px").trim().parse().ok()
+}
+
+fn parse_numbers(s: &str) -> Option<Vec<f32>> {
+    s.split(|c: char| c.is_ascii_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.parse().ok())
+        .collect()
+}
+
+fn parse_transform(s: &str) -> Option<Transform> {
+    let mut transform = IDENTITY;
+
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        let open = rest.find('(')?;
+        let close = rest.find(')')?;
+
+        let name = rest[..open].trim_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        let args = parse_numbers(&rest[open + 1..close])?;
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+
+        let t = match (name, args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => [a, b, c, d, e, f],
+            ("translate", &[tx]) => [1.0, 0.0, 0.0, 1.0, tx, 0.0],
+            ("translate", &[tx, ty]) => [1.0, 0.0, 0.0, 1.0, tx, ty],
+            ("scale", &[s]) => [s, 0.0, 0.0, s, 0.0, 0.0],
+            ("scale", &[sx, sy]) => [sx, 0.0, 0.0, sy, 0.0, 0.0],
+            ("rotate", &[a]) => rotation(a, 0.0, 0.0),
+            ("rotate", &[a, cx, cy]) => rotation(a, cx, cy),
+            ("skewX", &[a]) => [1.0, 0.0, (a * PI / 180.0).tan(), 1.0, 0.0, 0.0],
+            ("skewY", &[a]) => [1.0, (a * PI / 180.0).tan(), 0.0, 1.0, 0.0, 0.0],
+            _ => return None,
+        };
+
+        transform = compose(transform, t);
+    }
+
+    Some(transform)
+}
+
+fn rotation(degrees: f32, cx: f32, cy: f32) -> Transform {
+    let (s, c) = degrees.to_radians().sin_cos();
+    [c, s, -s, c, cx - c * cx + s * cy, cy - s * cx - c * cy]
+}
+
+/// Transformation that applies `inner` first and then `outer`.
+fn compose(outer: Transform, inner: Transform) -> Transform {
+    let [a0, b0, c0, d0, e0, f0] = outer;
+    let [a1, b1, c1, d1, e1, f1] = inner;
+
+    [
+        a0 * a1 + c0 * b1,
+        b0 * a1 + d0 * b1,
+        a0 * c1 + c0 * d1,
+        b0 * c1 + d0 * d1,
+        a0 * e1 + c0 * f1 + e0,
+        b0 * e1 + d0 * f1 + f0,
+    ]
+}
+
+fn apply(t: &Transform, (x, y): (f32, f32)) -> (f32, f32) {
+    (t[0] * x + t[2] * y + t[4], t[1] * x + t[3] * y + t[5])
+}
@@ -0,0 +1,205 @@
+//! Build containers from the outlines of the glyphs of a string of text.
+//!
+//! The text is laid out on a single line starting at the origin with the top
+//! of the line at `y = 0`, kerning pairs from either the `kern` or the `GPOS`
+//! table are applied. Glyph outlines are flattened and their contours are
+//! nested with the nonzero rule, so the counters of letters like "o" and "a"
+//! become holes.
+
+use std::{error::Error, fmt, fs, io, path::Path};
+
+use ttf_parser::{
+    gpos::{PairAdjustment, PositioningSubtable},
+    Face, FaceParsingError, GlyphId, OutlineBuilder, Tag,
+};
+
+use crate::{
+    nesting::{self, FillRule},
+    path, Polyline,
+};
+
+#[derive(Debug)]
+pub enum TextError {
+    Io(io::Error),
+    Font(FaceParsingError),
+}
+
+/// Layout parameters of a string of text.
+#[derive(Debug, Clone)]
+pub struct TextStyle {
+    /// Font size, i.e. the size of the em square, in output units.
+    pub size: f32,
+
+    /// Extra space added between consecutive glyphs, in output units.
+    pub letter_spacing: f32,
+
+    /// Max distance between the flattened outlines and the real curves.
+    pub tolerance: f32,
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextError::Io(e) => write!(f, "cannot read font: {}", e),
+            TextError::Font(e) => write!(f, "invalid font: {}", e),
+        }
+    }
+}
+
+impl Error for TextError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TextError::Io(e) => Some(e),
+            TextError::Font(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for TextError {
+    fn from(e: io::Error) -> Self {
+        TextError::Io(e)
+    }
+}
+
+impl From<FaceParsingError> for TextError {
+    fn from(e: FaceParsingError) -> Self {
+        TextError::Font(e)
+    }
+}
+
+/// Read the TTF/OTF font at the given path, see `outlines`.
+pub fn load(
+    font: impl AsRef<Path>,
+    text: &str,
+    style: &TextStyle,
+) -> Result<Vec<Polyline>, TextError> {
+    let data = fs::read(font)?;
+    outlines(&data, text, style)
+}
+
+/// Lay out the given text with the given font data and return the containers
+/// of its glyphs, in the order they appear in the text. Characters missing
+/// from the font are rendered with its `.notdef` glyph.
+pub fn outlines(font: &[u8], text: &str, style: &TextStyle) -> Result<Vec<Polyline>, TextError> {
+    let face = Face::parse(font, 0)?;
+    let scale = style.size / f32::from(face.units_per_em());
+    let baseline = f32::from(face.ascender()) * scale;
+
+    let mut containers = vec![];
+    let mut pen = 0.0;
+    let mut prev = None;
+
+    for c in text.chars() {
+        let glyph = face.glyph_index(c).unwrap_or(GlyphId(0));
+
+        if let Some(prev) = prev {
+            pen += f32::from(kerning(&face, prev, glyph)) * scale;
+        }
+
+        let mut builder = PathBuilder {
+            d: String::new(),
+            x: pen,
+            y: baseline,
+            scale,
+        };
+        if face.outline_glyph(glyph, &mut builder).is_some() {
+            // the path data is generated above so it's always valid
+            let subpaths = path::parse_subpaths(&builder.d, style.tolerance).unwrap();
+            containers.extend(nesting::nest(subpaths, FillRule::NonZero));
+        }
+
+        let advance = face.glyph_hor_advance(glyph).unwrap_or(0);
+        pen += f32::from(advance) * scale + style.letter_spacing;
+        prev = Some(glyph);
+    }
+
+    Ok(containers)
+}
+
+/// Horizontal adjustment between the given pair of glyphs in font units.
+fn kerning(face: &Face, left: GlyphId, right: GlyphId) -> i16 {
+    let tables = face.tables();
+
+    let kern = tables.kern.and_then(|kern| {
+        kern.subtables
+            .into_iter()
+            .filter(|st| st.horizontal && !st.variable && !st.has_cross_stream)
+            .find_map(|st| st.glyphs_kerning(left, right))
+    });
+
+    let gpos = || {
+        let gpos = tables.gpos?;
+        let feature = gpos.features.find(Tag::from_bytes(b"kern"))?;
+
+        feature.lookup_indices.into_iter().find_map(|index| {
+            let lookup = gpos.lookups.get(index)?;
+
+            (0..lookup.subtables.len()).find_map(|i| {
+                match lookup.subtables.get::<PositioningSubtable>(i)? {
+                    PositioningSubtable::Pair(pair) => pair_kerning(&pair, left, right),
+                    _ => None,
+                }
+            })
+        })
+    };
+
+    kern.or_else(gpos).unwrap_or(0)
+}
+
+fn pair_kerning(pair: &PairAdjustment, left: GlyphId, right: GlyphId) -> Option<i16> {
+    let records = match pair {
+        PairAdjustment::Format1 { coverage, sets } => sets.get(coverage.get(left)?)?.get(right)?,
+        PairAdjustment::Format2 {
+            coverage,
+            classes,
+            matrix,
+        } => {
+            coverage.get(left)?;
+            matrix.get((classes.0.get(left), classes.1.get(right)))?
+        }
+    };
+
+    Some(records.0.x_advance)
+}
+
+/// Collects the outline of a glyph as path data in output coordinates, which
+/// have the y axis pointing down unlike font units.
+struct PathBuilder {
+    d: String,
+    x: f32,
+    y: f32,
+    scale: f32,
+}
+
+impl PathBuilder {
+    fn point(&self, x: f32, y: f32) -> String {
+        format!("{},{} ", self.x + x * self.scale, self.y - y * self.scale)
+    }
+}
+
+impl OutlineBuilder for PathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.d += &format!("M {}", self.point(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.d += &format!("L {}", self.point(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.d += &format!("Q {}{}", self.point(x1, y1), self.point(x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.d += &format!(
+            "C {}{}{}",
+            self.point(x1, y1),
+            self.point(x2, y2),
+            self.point(x, y)
+        );
+    }
+
+    fn close(&mut self) {
+        self.d += "Z ";
+    }
+}
@@ -0,0 +1,128 @@
+//! Ordering of plotter strokes that keeps the travel with the pen up short.
+//!
+//! Strokes are first chained greedily by always moving to the closest free
+//! end, then the chain is improved with 2-opt moves which reverse runs of
+//! strokes when that shortens the travel.
+
+use crate::{grid::Grid, plot::Stroke, Bbox};
+
+/// Number of strokes after each stroke that 2-opt tries to reverse the run
+/// up to, which keeps each pass linear in the number of strokes.
+const TWO_OPT_WINDOW: usize = 32;
+
+/// Max number of 2-opt passes, most of the gain comes from the first ones.
+const TWO_OPT_PASSES: usize = 8;
+
+/// Reorder and reverse the given strokes so that the pen travels as little as
+/// possible starting from `start`, return where the pen ends up.
+pub fn optimize(strokes: &mut Vec<Stroke>, start: (f32, f32)) -> (f32, f32) {
+    if strokes.is_empty() {
+        return start;
+    }
+
+    nearest_neighbor(strokes, start);
+    two_opt(strokes, start);
+
+    // closed strokes can start from any of their vertices
+    let mut pos = start;
+    for i in 0..strokes.len() {
+        let next = strokes.get(i + 1).map(Stroke::start);
+        strokes[i].rotate_towards(pos, next);
+        pos = strokes[i].end();
+    }
+
+    pos
+}
+
+/// Distance traveled with the pen up to draw the given strokes in order
+/// starting from `start`.
+pub fn travel_length(strokes: &[Stroke], start: (f32, f32)) -> f32 {
+    let mut pos = start;
+    let mut length = 0.0;
+    for s in strokes {
+        length += dist(pos, s.start());
+        pos = s.end();
+    }
+    length
+}
+
+/// Chain the strokes by always drawing next the one with the closest end,
+/// reversing it if needed.
+fn nearest_neighbor(strokes: &mut Vec<Stroke>, start: (f32, f32)) {
+    // the ends of the i-th stroke have ids 2 * i and 2 * i + 1
+    let ends = strokes
+        .iter()
+        .flat_map(|s| [s.start(), s.end()])
+        .collect::<Vec<_>>();
+
+    let mut extent = Bbox::new(start.0, start.1);
+    for &(x, y) in &ends {
+        extent.expand(x, y);
+    }
+
+    let mut grid = Grid::default();
+    for &(x, y) in &ends {
+        grid.insert(Bbox::new(x, y), &extent, 0.0);
+    }
+
+    let mut free = strokes.drain(..).map(Some).collect::<Vec<_>>();
+    let mut pos = start;
+
+    while strokes.len() < free.len() {
+        let mut closest = (f32::INFINITY, 0);
+        grid.visit_nearby(pos.0, pos.1, f32::INFINITY, |id| {
+            let d = dist(pos, ends[id]);
+            if d < closest.0 {
+                closest = (d, id);
+            }
+            closest.0
+        });
+
+        let (_, id) = closest;
+        let i = id / 2;
+        grid.remove(2 * i);
+        grid.remove(2 * i + 1);
+
+        let mut stroke = free[i].take().unwrap();
+        if id % 2 == 1 {
+            stroke.reverse();
+        }
+        pos = stroke.end();
+        strokes.push(stroke);
+    }
+}
+
+/// Reverse runs of consecutive strokes, and each stroke in them, as long as
+/// that shortens the travel.
+fn two_opt(strokes: &mut [Stroke], start: (f32, f32)) {
+    for _ in 0..TWO_OPT_PASSES {
+        let mut improved = false;
+
+        for i in 0..strokes.len() {
+            for j in i + 1..strokes.len().min(i + TWO_OPT_WINDOW) {
+                let prev = if i == 0 { start } else { strokes[i - 1].end() };
+                let next = strokes.get(j + 1).map(Stroke::start);
+
+                let travel = |a: (f32, f32), b: Option<(f32, f32)>| b.map_or(0.0, |b| dist(a, b));
+                let before = dist(prev, strokes[i].start()) + travel(strokes[j].end(), next);
+                let after = dist(prev, strokes[j].end()) + travel(strokes[i].start(), next);
+
+                if after < before - 1e-3 {
+                    strokes[i..=j].reverse();
+                    for s in &mut strokes[i..=j] {
+                        s.reverse();
+                    }
+                    improved = true;
+                }
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+}
+
+fn dist((x0, y0): (f32, f32), (x1, y1): (f32, f32)) -> f32 {
+    (x1 - x0).hypot(y1 - y0)
+}
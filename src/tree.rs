@@ -0,0 +1,187 @@
+//! Save packed trees to disk and load them back, so that they can be rendered
+//! again with other palettes or outputs without packing them again.
+//!
+//! Each node stores its container, color index and fill together with its
+//! children. The spatial index and the occupied area are rebuilt on load, so
+//! a loaded tree can also be packed further.
+
+use std::{
+    error::Error,
+    fmt,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{grid::Grid, Circle, PackShape, Packable, Settings, Shape};
+
+#[derive(Debug)]
+pub enum TreeError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+}
+
+/// On disk format of a tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+
+    /// Compact binary encoding, not meant to be read by other programs.
+    Binary,
+}
+
+/// Packed roots together with the settings they were packed with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "S: Serialize, C: Serialize",
+    deserialize = "S: Deserialize<'de>, C: Deserialize<'de>"
+))]
+pub struct Packing<S: Shape, C: Packable = Circle> {
+    pub settings: Settings,
+    pub roots: Vec<PackShape<S, C>>,
+}
+
+/// What's stored of each node of the tree.
+#[derive(Serialize, Deserialize)]
+struct Node<S, T> {
+    container: S,
+    color: usize,
+    fill: Option<String>,
+    children: T,
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::Io(e) => write!(f, "cannot access tree: {}", e),
+            TreeError::Json(e) => write!(f, "invalid tree: {}", e),
+            TreeError::Binary(e) => write!(f, "invalid tree: {}", e),
+        }
+    }
+}
+
+impl Error for TreeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TreeError::Io(e) => Some(e),
+            TreeError::Json(e) => Some(e),
+            TreeError::Binary(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for TreeError {
+    fn from(e: io::Error) -> Self {
+        TreeError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TreeError {
+    fn from(e: serde_json::Error) -> Self {
+        TreeError::Json(e)
+    }
+}
+
+impl From<bincode::Error> for TreeError {
+    fn from(e: bincode::Error) -> Self {
+        TreeError::Binary(e)
+    }
+}
+
+impl Format {
+    /// JSON if the path ends with `.json`, binary otherwise.
+    pub fn from_path(path: &Path) -> Format {
+        if path.extension().is_some_and(|e| e == "json") {
+            Format::Json
+        } else {
+            Format::Binary
+        }
+    }
+}
+
+impl<S: Shape + Serialize, C: Packable + Serialize> Serialize for PackShape<S, C> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        Node {
+            container: &self.container,
+            color: self.color,
+            fill: self.fill.clone(),
+            children: &self.children,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, S, C> Deserialize<'de> for PackShape<S, C>
+where
+    S: Shape + Deserialize<'de>,
+    C: Packable + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let node = Node::<S, Vec<PackShape<C, C>>>::deserialize(deserializer)?;
+
+        let mut shape = PackShape::new(node.container);
+        shape.color = node.color;
+        shape.fill = node.fill;
+        shape.occupied_area = node.children.iter().map(|c| c.area()).sum();
+
+        // children are never smaller than the cells the grid had while packing
+        let min_cell_size = node
+            .children
+            .iter()
+            .map(|c| {
+                let bbox = c.bbox();
+                bbox.width().min(bbox.height())
+            })
+            .fold(f32::INFINITY, f32::min);
+
+        let mut index = Grid::default();
+        let extent = shape.container.bbox();
+        for c in &node.children {
+            index.insert(c.bbox(), &extent, min_cell_size);
+        }
+        shape.index = index;
+        shape.children = node.children;
+
+        Ok(shape)
+    }
+}
+
+/// Write the given value, usually a `Packing`, in the given format.
+pub fn write<T: Serialize>(out: impl Write, value: &T, format: Format) -> Result<(), TreeError> {
+    match format {
+        Format::Json => serde_json::to_writer(out, value)?,
+        Format::Binary => bincode::serialize_into(out, value)?,
+    }
+    Ok(())
+}
+
+/// Read a value, usually a `Packing`, in the given format. Trailing data is
+/// ignored by the binary format only.
+pub fn read<T: DeserializeOwned>(data: &[u8], format: Format) -> Result<T, TreeError> {
+    // reading from a slice, bincode checks lengths against the data left
+    // instead of trusting them
+    Ok(match format {
+        Format::Json => serde_json::from_slice(data)?,
+        Format::Binary => bincode::deserialize(data)?,
+    })
+}
+
+/// Write the given value at the given path, in the format given by its
+/// extension, see `Format::from_path`.
+pub fn save<T: Serialize>(path: impl AsRef<Path>, value: &T) -> Result<(), TreeError> {
+    let path = path.as_ref();
+    let mut out = BufWriter::new(File::create(path)?);
+    write(&mut out, value, Format::from_path(path))?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Read the value at the given path, in the format given by its extension,
+/// see `Format::from_path`.
+pub fn load<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, TreeError> {
+    let path = path.as_ref();
+    read(&fs::read(path)?, Format::from_path(path))
+}
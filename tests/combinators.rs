@@ -0,0 +1,48 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{
+    dump_svg, pack, Circle, Difference, Intersection, PackShape, Settings, Shape, StopCriterion,
+};
+
+#[test]
+fn intersection_of_disjoint_circles_is_empty() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let shape = Intersection::new(Circle::new(0.0, 0.0, 10.0), Circle::new(50.0, 0.0, 10.0));
+
+    assert!(shape.is_empty());
+    assert_eq!(shape.area(), 0.0);
+    assert_eq!(shape.random_point(&mut rng), shape.center());
+
+    let mut svg = vec![];
+    shape.write_svg(&mut svg, "red", "none").unwrap();
+    assert!(svg.is_empty());
+
+    // nothing fits, the packing stops once it stalls
+    let cfg = Settings {
+        min_radius: 1.0,
+        padding: 0.0,
+        stop: Some(StopCriterion::Stall(100)),
+        ..Settings::default()
+    };
+    let mut root = PackShape::<_, Circle>::new(shape);
+    pack(&mut root, &cfg, &mut rng);
+    assert!(root.children().is_empty());
+
+    let mut svg = vec![];
+    dump_svg(&mut svg, std::slice::from_ref(&root), &cfg).unwrap();
+    assert!(!String::from_utf8(svg).unwrap().contains("<path"));
+}
+
+#[test]
+fn overlapping_combinations_are_not_empty() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let lens = Intersection::new(Circle::new(0.0, 0.0, 10.0), Circle::new(10.0, 0.0, 10.0));
+    assert!(!lens.is_empty());
+    let (x, y) = lens.random_point(&mut rng);
+    assert!(lens.sdf(x, y) <= 0.0);
+
+    let covered = Difference::new(Circle::new(0.0, 0.0, 10.0), Circle::new(0.0, 0.0, 20.0));
+    assert!(covered.is_empty());
+    assert_eq!(covered.random_point(&mut rng), covered.center());
+}
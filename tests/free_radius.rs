@@ -0,0 +1,71 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{pack, Bbox, Circle, PackShape, Settings, Shape, StopCriterion};
+
+/// Radius `fit` should find when shapes aren't packed inside other shapes,
+/// scanning all the children.
+fn brute_force(
+    root: &PackShape<Bbox, Circle>,
+    x: f32,
+    y: f32,
+    scale: f32,
+    cfg: &Settings,
+) -> Option<f32> {
+    let radius = root
+        .children()
+        .iter()
+        .map(|c| c.sdf(x, y) - cfg.padding)
+        .fold(scale, f32::min);
+
+    if radius < cfg.min_radius {
+        None
+    } else {
+        Some(radius)
+    }
+}
+
+#[test]
+fn fit_matches_a_scan_of_all_children() {
+    let mut rng = ChaCha8Rng::seed_from_u64(11);
+    let cfg = Settings {
+        min_radius: 2.0,
+        padding: 1.0,
+        inside: false,
+        stop: Some(StopCriterion::Placed(400)),
+        ..Settings::default()
+    };
+
+    let mut bbox = Bbox::new(0.0, 0.0);
+    bbox.expand(400.0, 300.0);
+    let mut root = PackShape::<Bbox, Circle>::new(bbox);
+
+    // children spanning many cells of the grid, placed before it's refined
+    for &(x, y, r) in &[
+        (100.0, 100.0, 80.0),
+        (300.0, 200.0, 60.0),
+        (330.0, 40.0, 35.0),
+    ] {
+        assert!(root.pack(PackShape::new(Circle::new(x, y, r)), &cfg));
+    }
+    pack(&mut root, &cfg, &mut rng);
+    assert!(root.children().len() > 100);
+
+    // and after it is, bigger than the cells
+    assert!(root.pack(PackShape::new(Circle::new(-200.0, 150.0, 180.0)), &cfg));
+
+    for _ in 0..5000 {
+        let (x, y) = (rng.gen_range(-50.0..450.0), rng.gen_range(-50.0..350.0));
+        let scale = rng.gen_range(1.0..200.0);
+
+        let (fit, expected) = (
+            root.fit(x, y, scale, &cfg),
+            brute_force(&root, x, y, scale, &cfg),
+        );
+        match (fit, expected) {
+            (Some(a), Some(b)) => assert!((a - b).abs() < 1e-4, "{:?}: {} != {}", (x, y), a, b),
+            (None, None) => {}
+            _ => panic!("{:?}: {:?} != {:?}", (x, y), fit, expected),
+        }
+    }
+}
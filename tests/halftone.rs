@@ -0,0 +1,29 @@
+use image::{DynamicImage, GrayImage, Luma};
+
+use circle_packing::{halftone::RadiusField, Bbox};
+
+#[test]
+fn radius_follows_the_brightness() {
+    let mut area = Bbox::new(0.0, 0.0);
+    area.expand(256.0, 10.0);
+
+    // a horizontal gradient from black to white, one pixel per unit
+    let image = DynamicImage::ImageLuma8(GrayImage::from_fn(256, 1, |i, _| Luma([i as u8])));
+    let mut field = RadiusField::new(&image, area, 2.0, 12.0);
+
+    assert!((field.radius(0.5, 5.0) - 2.0).abs() < 1e-3);
+    assert!((field.radius(255.5, 5.0) - 12.0).abs() < 1e-3);
+    assert!((field.radius(127.5, 5.0) - (2.0 + 10.0 * 127.0 / 255.0)).abs() < 1e-3);
+
+    let radii = (0..256)
+        .map(|x| field.radius(x as f32 + 0.5, 5.0))
+        .collect::<Vec<_>>();
+    assert!(radii.windows(2).all(|w| w[0] < w[1]));
+
+    // outside of the area the closest pixel is used
+    assert!((field.radius(-50.0, -5.0) - 2.0).abs() < 1e-3);
+
+    field.invert = true;
+    assert!((field.radius(0.5, 5.0) - 12.0).abs() < 1e-3);
+    assert!((field.radius(255.5, 5.0) - 2.0).abs() < 1e-3);
+}
@@ -0,0 +1,45 @@
+use std::{fs, process::Command};
+
+use image::{DynamicImage, GrayImage, Luma};
+
+use circle_packing::{mask::MaskError, Mask, Shape};
+
+#[test]
+fn empty_masks_are_rejected() {
+    let black = DynamicImage::ImageLuma8(GrayImage::new(8, 8));
+    assert!(matches!(
+        Mask::from_image(&black, 127),
+        Err(MaskError::Empty { threshold: 127 })
+    ));
+
+    let none = DynamicImage::ImageLuma8(GrayImage::new(0, 0));
+    assert!(Mask::from_image(&none, 0).is_err());
+
+    let mut dot = GrayImage::new(8, 8);
+    dot.put_pixel(3, 5, Luma([255]));
+    let mask = Mask::from_image(&DynamicImage::ImageLuma8(dot), 127).unwrap();
+    assert_eq!(mask.area(), 1.0);
+
+    let (x, y) = mask.random_point(&mut rand::thread_rng());
+    assert!((3.0..4.0).contains(&x) && (5.0..6.0).contains(&y));
+}
+
+#[test]
+fn cli_exits_on_empty_mask() {
+    let dir = std::env::temp_dir().join(format!("circle-packing-mask-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let black = dir.join("black.png");
+    GrayImage::new(16, 16).save(&black).unwrap();
+
+    let out = Command::new(env!("CARGO_BIN_EXE_circle-packing"))
+        .arg("-c")
+        .arg(&black)
+        .arg("-o")
+        .arg(dir.join("out.svg"))
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("no pixel is brighter"));
+}
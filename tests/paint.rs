@@ -0,0 +1,53 @@
+use image::{Rgb, RgbImage};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{
+    pack,
+    paint::{self, Sampling},
+    Bbox, Circle, PackShape, Settings, Shape, StopCriterion,
+};
+
+#[test]
+fn fill_follows_the_image() {
+    let mut area = Bbox::new(0.0, 0.0);
+    area.expand(200.0, 100.0);
+
+    // dark on the left half, bright on the right one
+    let image = RgbImage::from_fn(2, 1, |i, _| match i {
+        0 => Rgb([10, 20, 30]),
+        _ => Rgb([250, 240, 230]),
+    });
+
+    let cfg = Settings {
+        min_radius: 4.0,
+        padding: 1.0,
+        stop: Some(StopCriterion::Placed(40)),
+        ..Settings::default()
+    };
+
+    for sampling in [Sampling::Center, Sampling::Mean] {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let mut root = PackShape::<Bbox, Circle>::new(area.clone());
+        pack(&mut root, &cfg, &mut rng);
+        paint::paint(&mut root, &image, &area, sampling);
+
+        assert!(!root.children().is_empty());
+        for shape in root.children() {
+            let bbox = shape.bbox();
+            let fill = shape.fill.as_deref();
+            if bbox.x1() < 100.0 {
+                assert_eq!(fill, Some("#0a141e"));
+            } else if bbox.x0() > 100.0 {
+                assert_eq!(fill, Some("#faf0e6"));
+            } else if sampling == Sampling::Center {
+                let expected = if shape.center().0 < 100.0 {
+                    "#0a141e"
+                } else {
+                    "#faf0e6"
+                };
+                assert_eq!(fill, Some(expected));
+            }
+        }
+    }
+}
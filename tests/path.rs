@@ -0,0 +1,198 @@
+use circle_packing::path::{parse, parse_subpaths, ParseError};
+
+const TOLERANCE: f32 = 0.01;
+
+fn subpaths(d: &str) -> Vec<Vec<(f32, f32)>> {
+    parse_subpaths(d, TOLERANCE).unwrap()
+}
+
+fn assert_same(a: &str, b: &str) {
+    let (pa, pb) = (subpaths(a), subpaths(b));
+    assert_eq!(pa.len(), pb.len(), "{} vs {}", a, b);
+
+    for (sa, sb) in pa.iter().zip(&pb) {
+        assert_eq!(sa.len(), sb.len(), "{} vs {}", a, b);
+        for (p, q) in sa.iter().zip(sb) {
+            assert!(
+                (p.0 - q.0).abs() < 1e-3 && (p.1 - q.1).abs() < 1e-3,
+                "{} vs {}: {:?} != {:?}",
+                a,
+                b,
+                p,
+                q
+            );
+        }
+    }
+}
+
+#[test]
+fn relative_and_absolute_commands() {
+    assert_eq!(
+        subpaths("M 10 10 L 20 10 L 20 20 Z"),
+        [[(10.0, 10.0), (20.0, 10.0), (20.0, 20.0)]]
+    );
+    assert_same("M 10 10 L 20 10 L 20 20 Z", "m 10 10 l 10 0 l 0 10 z");
+    assert_same(
+        "M 0 0 C 0 10 10 10 10 0 Q 15 5 20 0",
+        "m 0 0 c 0 10 10 10 10 0 q 5 5 10 0",
+    );
+    assert_same("M 0 0 A 5 5 0 0 1 10 0", "m 0 0 a 5 5 0 0 1 10 0");
+}
+
+#[test]
+fn implicit_repeats() {
+    // extra pairs after a moveto are linetos, relative if the moveto is
+    assert_eq!(
+        subpaths("M 0 0 10 0 10 10"),
+        [[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]]
+    );
+    assert_eq!(
+        subpaths("m 1 1 10 0 0 10"),
+        [[(1.0, 1.0), (11.0, 1.0), (11.0, 11.0)]]
+    );
+    assert_same("M 0 0 L 10 0 10 10 0 10", "M 0 0 L 10 0 L 10 10 L 0 10");
+    assert_same("M 0 0 h 5 5 v 5 5", "M 0 0 H 5 H 10 V 5 V 10");
+    assert_same(
+        "M 0 0 c 0 5 5 5 5 0 0 -5 5 -5 5 0",
+        "M 0 0 C 0 5 5 5 5 0 C 5 -5 10 -5 10 0",
+    );
+
+    // numbers don't need separators when they can't be confused
+    assert_same("M0-1.5.5e1 2", "M 0 -1.5 L 5 2");
+    assert_same("M0 0a5 5 0 1010 0", "M 0 0 A 5 5 0 1 0 10 0");
+}
+
+#[test]
+fn horizontal_and_vertical_lines() {
+    assert_eq!(
+        subpaths("M 1 2 H 10 V 5 h -4 v 3"),
+        [[(1.0, 2.0), (10.0, 2.0), (10.0, 5.0), (6.0, 5.0), (6.0, 8.0)]]
+    );
+}
+
+#[test]
+fn smooth_curves_reflect_the_previous_control_point() {
+    assert_same(
+        "M 0 0 C 0 10 10 10 10 0 S 20 -10 20 0",
+        "M 0 0 C 0 10 10 10 10 0 C 10 -10 20 -10 20 0",
+    );
+    assert_same(
+        "M 0 0 Q 5 10 10 0 T 20 0 t 10 0",
+        "M 0 0 Q 5 10 10 0 Q 15 -10 20 0 Q 25 10 30 0",
+    );
+
+    // without a previous curve of the same kind the control point is the
+    // current point
+    assert_same(
+        "M 0 0 L 10 0 S 20 10 20 0",
+        "M 0 0 L 10 0 C 10 0 20 10 20 0",
+    );
+    assert_same(
+        "M 0 0 C 0 10 10 10 10 0 T 20 0",
+        "M 0 0 C 0 10 10 10 10 0 Q 10 0 20 0",
+    );
+    assert_same(
+        "M 0 0 Q 5 10 10 0 S 20 10 20 0",
+        "M 0 0 Q 5 10 10 0 C 10 0 20 10 20 0",
+    );
+}
+
+#[test]
+fn arcs() {
+    let half = &subpaths("M 0 0 A 10 10 0 0 1 20 0")[0];
+    assert!(half.len() > 10);
+    assert_eq!(half[0], (0.0, 0.0));
+    assert_eq!(half[half.len() - 1], (20.0, 0.0));
+    for &(x, y) in half {
+        assert!(((x - 10.0).hypot(y) - 10.0).abs() < 1e-3, "{:?}", (x, y));
+        assert!(y <= 1e-3);
+    }
+
+    // the sweep flag picks the side, the large arc flag the longer way round
+    let other = &subpaths("M 0 0 A 10 10 0 0 0 20 0")[0];
+    assert!(other.iter().all(|&(_, y)| y >= -1e-3));
+    let large = &subpaths("M 0 0 A 10 10 0 1 1 10 10")[0];
+    let small = &subpaths("M 0 0 A 10 10 0 0 1 10 10")[0];
+    assert!(large.len() > 2 * small.len());
+
+    // radii too small to connect the endpoints are scaled up
+    assert_same("M 0 0 A 1 1 0 0 1 20 0", "M 0 0 A 10 10 0 0 1 20 0");
+    assert_same("M 0 0 A 2 1 0 0 1 40 0", "M 0 0 A 20 10 0 0 1 40 0");
+
+    // zero radii are straight lines, negative ones are taken as positive
+    assert_eq!(
+        subpaths("M 0 0 A 0 10 0 0 1 20 0 L 20 20"),
+        [[(0.0, 0.0), (20.0, 0.0), (20.0, 20.0)]]
+    );
+    assert_same("M 0 0 A -10 -10 0 0 1 20 0", "M 0 0 A 10 10 0 0 1 20 0");
+
+    // an arc ending where it starts is skipped
+    assert_eq!(
+        subpaths("M 0 0 L 10 0 A 5 5 0 0 1 10 0 L 10 10"),
+        [[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]]
+    );
+}
+
+#[test]
+fn several_subpaths() {
+    let square = "M 0 0 L 10 0 L 10 10 L 0 10 Z";
+    let paths = subpaths(&format!("{} M 20 20 L 30 20 L 30 30 z", square));
+    assert_eq!(paths.len(), 2);
+    assert_eq!(paths[1], [(20.0, 20.0), (30.0, 20.0), (30.0, 30.0)]);
+
+    // a relative moveto after a closepath is relative to the start of the
+    // closed subpath
+    assert_eq!(
+        subpaths("M 10 10 l 10 0 l 0 10 z m 5 5 l 1 0 l 0 1 z")[1],
+        [(15.0, 15.0), (16.0, 15.0), (16.0, 16.0)]
+    );
+
+    // drawing after a closepath starts a new subpath there
+    assert_eq!(
+        subpaths("M 5 5 L 10 5 L 10 10 Z L 0 10 l 0 -5"),
+        [
+            vec![(5.0, 5.0), (10.0, 5.0), (10.0, 10.0)],
+            vec![(5.0, 5.0), (0.0, 10.0), (0.0, 5.0)],
+        ]
+    );
+
+    // a moveto without a closepath ends the subpath too, and `parse` drops
+    // the ones with less than 3 points
+    assert_eq!(subpaths("M 0 0 L 5 5 M 1 1 L 2 2 L 3 1").len(), 2);
+    assert_eq!(
+        parse("M 0 0 L 5 5 M 1 1 L 2 2 L 3 1", TOLERANCE)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn errors() {
+    let error = |d: &str| parse_subpaths(d, TOLERANCE).unwrap_err();
+
+    assert_eq!(error("L 10 10"), ParseError::MissingMoveTo { pos: 0 });
+    assert_eq!(error("  z"), ParseError::MissingMoveTo { pos: 2 });
+    assert_eq!(
+        error("M 10"),
+        ParseError::MissingArgument { pos: 0, cmd: 'M' }
+    );
+    assert_eq!(
+        error("M 0 0 L 5 x"),
+        ParseError::MissingArgument { pos: 6, cmd: 'L' }
+    );
+    assert_eq!(
+        error("M 0 0 A 1 1 0 2 0 5 5"),
+        ParseError::MissingArgument { pos: 6, cmd: 'A' }
+    );
+    assert_eq!(
+        error("M 0 0 # 1"),
+        ParseError::UnexpectedChar { pos: 6, ch: '#' }
+    );
+    assert_eq!(
+        error("M 0 0 X 1 1"),
+        ParseError::UnexpectedChar { pos: 6, ch: 'X' }
+    );
+
+    assert_eq!(subpaths(""), Vec::<Vec<(f32, f32)>>::new());
+}
@@ -0,0 +1,64 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{pack, pdf, Bbox, Circle, PackShape, Settings, StopCriterion};
+
+/// The text between `prefix` and the end of its line.
+fn line_after<'a>(doc: &'a str, prefix: &str) -> &'a str {
+    let start = doc.find(prefix).unwrap() + prefix.len();
+    doc[start..].lines().next().unwrap()
+}
+
+#[test]
+fn xref_points_at_the_objects() {
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+    let cfg = Settings {
+        min_radius: 4.0,
+        padding: 1.0,
+        stop: Some(StopCriterion::Placed(30)),
+        ..Settings::default()
+    };
+
+    let mut bbox = Bbox::new(0.0, 0.0);
+    bbox.expand(200.0, 100.0);
+    let mut root = PackShape::<Bbox, Circle>::new(bbox);
+    pack(&mut root, &cfg, &mut rng);
+
+    let mut out = vec![];
+    pdf::dump_pdf(&mut out, std::slice::from_ref(&root), &cfg, &pdf::Page::A4).unwrap();
+    let doc = String::from_utf8(out).unwrap();
+
+    assert!(doc.starts_with("%PDF-"));
+    assert!(doc.trim_end().ends_with("%%EOF"));
+
+    let xref: usize = line_after(&doc, "startxref\n").parse().unwrap();
+    assert!(doc[xref..].starts_with("xref\n"));
+
+    let mut lines = doc[xref..].lines().skip(1);
+    let count: usize = lines
+        .next()
+        .unwrap()
+        .strip_prefix("0 ")
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(lines.next(), Some("0000000000 65535 f "));
+    for i in 1..count {
+        let entry = lines.next().unwrap();
+        assert_eq!(entry.len(), 19, "{:?}", entry);
+        let offset: usize = entry[..10].parse().unwrap();
+        assert!(doc[offset..].starts_with(&format!("{} 0 obj\n", i)));
+    }
+    assert_eq!(
+        line_after(&doc, "/Size "),
+        format!("{} /Root 1 0 R >>", count)
+    );
+
+    // the stream is as long as declared
+    let length: usize = line_after(&doc, "/Length ")
+        .trim_end_matches(" >>")
+        .parse()
+        .unwrap();
+    let start = doc.find("stream\n").unwrap() + "stream\n".len();
+    assert!(doc[start + length..].starts_with("endstream"));
+}
@@ -0,0 +1,115 @@
+use std::ops::ControlFlow;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{
+    pack, pack_with_observer,
+    progress::{Observer, Progress},
+    Bbox, Circle, PackShape, Placement, RegularPolygon, Settings, Shape, StopCriterion,
+};
+
+fn coverage(placement: Placement) -> f32 {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let mut root = PackShape::new(RegularPolygon::new(150.0, 100.0, 90.0, 6));
+    let cfg = Settings {
+        min_radius: 2.0,
+        padding: 1.0,
+        stop: Some(StopCriterion::Stall(200)),
+        placement,
+        ..Settings::default()
+    };
+
+    pack(&mut root, &cfg, &mut rng);
+    root.occupied_area() / root.area()
+}
+
+#[test]
+fn best_of_packs_denser() {
+    let first = coverage(Placement::First);
+    let best = coverage(Placement::BestOf {
+        k: 16,
+        anneal_to: None,
+    });
+
+    assert!(best > first, "{} <= {}", best, first);
+}
+
+#[test]
+fn best_of_anneals_with_coverage() {
+    let placement: Placement = "best-of:32:4".parse().unwrap();
+
+    assert_eq!(placement.candidates(0.0), 32);
+    assert_eq!(placement.candidates(0.5), 18);
+    assert_eq!(placement.candidates(1.0), 4);
+    assert_eq!(placement.to_string().parse(), Ok(placement));
+    assert!("best-of:0".parse::<Placement>().is_err());
+}
+
+#[test]
+fn placement_rejects_extra_segments() {
+    for placement in [
+        "best-of:4:8:junk",
+        "best-of:4:junk",
+        "first:2",
+        "largest-empty:",
+    ] {
+        assert!(
+            placement.parse::<Placement>().is_err(),
+            "{} was accepted",
+            placement
+        );
+    }
+
+    assert_eq!("first".parse(), Ok(Placement::First));
+    assert_eq!("largest-empty".parse(), Ok(Placement::LargestEmpty));
+}
+
+/// Records the radius of the placed circles.
+struct Radii(Vec<f32>);
+
+impl Observer<Circle> for Radii {
+    fn placed(&mut self, shape: &PackShape<Circle>, _progress: &Progress) -> ControlFlow<()> {
+        self.0.push(shape.scale());
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn largest_empty_places_in_decreasing_order() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let mut bbox = Bbox::new(0.0, 0.0);
+    bbox.expand(300.0, 200.0);
+    let mut root = PackShape::new(bbox);
+    let cfg = Settings {
+        min_radius: 2.0,
+        padding: 1.0,
+        inside: false,
+        stop: Some(StopCriterion::Stall(1)),
+        placement: Placement::LargestEmpty,
+        ..Settings::default()
+    };
+
+    let mut radii = Radii(vec![]);
+    let child = Circle::new(0.0, 0.0, 1.0);
+    let progress = pack_with_observer(
+        &mut root,
+        &child,
+        &cfg,
+        |_, _| f32::INFINITY,
+        &mut radii,
+        &mut rng,
+    );
+
+    assert_eq!(progress.stall, 0);
+    assert!(radii.0.len() > 50, "{}", radii.0.len());
+    // gaps are found within a tenth of the minimum radius
+    for w in radii.0.windows(2) {
+        assert!(w[1] <= w[0] + 0.2, "{} after {}", w[1], w[0]);
+    }
+    assert!(*radii.0.last().unwrap() >= cfg.min_radius);
+    assert_eq!(
+        "largest-empty".parse::<Placement>(),
+        Ok(Placement::LargestEmpty)
+    );
+}
@@ -0,0 +1,163 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{
+    pack,
+    plot::{self, Fill, PlotSettings, Stroke},
+    travel, Circle, PackShape, Settings, Shape, StopCriterion,
+};
+
+#[test]
+fn fill_rejects_invalid_spacing() {
+    for fill in [
+        "hatch:0",
+        "hatch:-2",
+        "hatch:nan",
+        "hatch:inf",
+        "hatch:2:nan",
+        "concentric:0",
+        "concentric:-1",
+        "concentric:NaN",
+    ] {
+        assert!(fill.parse::<Fill>().is_err(), "{} was accepted", fill);
+    }
+
+    assert_eq!(
+        "hatch:2".parse(),
+        Ok(Fill::Hatch {
+            spacing: 2.0,
+            angle: 45f32.to_radians()
+        })
+    );
+    assert_eq!(
+        "concentric:1.5".parse(),
+        Ok(Fill::Concentric { spacing: 1.5 })
+    );
+}
+
+fn packed() -> (PackShape<Circle>, Settings) {
+    let mut rng = ChaCha8Rng::seed_from_u64(7);
+    let mut root = PackShape::new(Circle::new(50.0, 50.0, 50.0));
+    let cfg = Settings {
+        min_radius: 4.0,
+        padding: 1.0,
+        stop: Some(StopCriterion::Placed(20)),
+        ..Settings::default()
+    };
+    pack(&mut root, &cfg, &mut rng);
+    (root, cfg)
+}
+
+#[test]
+fn invalid_spacing_fills_nothing() {
+    let (root, cfg) = packed();
+    let lines = |fill| {
+        let plot = PlotSettings {
+            fill,
+            ..PlotSettings::default()
+        };
+        let mut out = vec![];
+        plot::dump_gcode(&mut out, std::slice::from_ref(&root), &cfg, &plot).unwrap();
+        out
+    };
+
+    let expected = lines(Fill::None);
+    for spacing in [0.0, -1.0, f32::NAN] {
+        assert_eq!(lines(Fill::Concentric { spacing }), expected);
+        assert_eq!(
+            lines(Fill::Hatch {
+                spacing,
+                angle: 0.0
+            }),
+            expected
+        );
+    }
+}
+
+/// Pen commands of the given output, mapped to `up` and `down`.
+fn pen_commands(out: &[u8], up: &str, down: &str) -> Vec<&'static str> {
+    String::from_utf8_lossy(out)
+        .split(['\n', ';'])
+        .filter_map(|c| match c.trim() {
+            c if c == up => Some("up"),
+            c if c == down => Some("down"),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn hpgl_and_gcode_lift_the_pen_alike() {
+    let (root, cfg) = packed();
+    let plot = PlotSettings {
+        fill: Fill::Hatch {
+            spacing: 2.0,
+            angle: 0.3,
+        },
+        ..PlotSettings::default()
+    };
+    let roots = std::slice::from_ref(&root);
+
+    let mut hpgl = vec![];
+    plot::dump_hpgl(&mut hpgl, roots, &cfg, &plot).unwrap();
+    let mut gcode = vec![];
+    plot::dump_gcode(&mut gcode, roots, &cfg, &plot).unwrap();
+
+    let hpgl = pen_commands(&hpgl, "PU", "PD");
+    let gcode = pen_commands(&gcode, &plot.pen_up, &plot.pen_down);
+    assert!(hpgl.len() > 40);
+    assert_eq!(hpgl, gcode);
+}
+
+#[test]
+fn optimized_travel_is_shorter() {
+    let mut rng = ChaCha8Rng::seed_from_u64(9);
+    let lines = (0..300)
+        .map(|_| {
+            let (x, y) = (rng.gen_range(0.0..500.0), rng.gen_range(0.0..500.0));
+            Stroke::Line(vec![(x, y), (x + rng.gen_range(-20.0..20.0), y + 5.0)])
+        })
+        .collect::<Vec<_>>();
+
+    let mut optimized = lines.clone();
+    travel::optimize(&mut optimized, (0.0, 0.0));
+    assert_eq!(optimized.len(), lines.len());
+
+    let before = travel::travel_length(&lines, (0.0, 0.0));
+    let after = travel::travel_length(&optimized, (0.0, 0.0));
+    assert!(after < before / 5.0, "{} vs {}", after, before);
+}
+
+#[test]
+fn optimized_layers_travel_less_than_kept_order() {
+    let (root, cfg) = packed();
+    let roots = std::slice::from_ref(&root);
+    let plot = PlotSettings {
+        fill: Fill::Hatch {
+            spacing: 3.0,
+            angle: 0.0,
+        },
+        ..PlotSettings::default()
+    };
+    let kept = PlotSettings {
+        optimize_travel: false,
+        ..plot.clone()
+    };
+
+    // the pen starts at the top left corner of the drawing
+    let bbox = root.bbox();
+    let start = (bbox.x0(), bbox.y1());
+    let length = |layers: Vec<plot::Layer>| {
+        let mut pos = start;
+        let mut length = 0.0;
+        for layer in &layers {
+            length += travel::travel_length(&layer.strokes, pos);
+            pos = layer.strokes.last().map_or(pos, Stroke::end);
+        }
+        length
+    };
+
+    let optimized = length(plot::layers(roots, &cfg, &plot));
+    let kept = length(plot::layers(roots, &cfg, &kept));
+    assert!(optimized <= kept, "{} > {}", optimized, kept);
+}
@@ -0,0 +1,160 @@
+use circle_packing::{
+    nesting::{self, FillRule},
+    Polyline, Shape,
+};
+
+fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Polyline {
+    Polyline::new(vec![(x0, y0), (x1, y0), (x1, y1), (x0, y1)]).unwrap()
+}
+
+/// The points of a rectangle, clockwise if `clockwise` in a y-down system.
+fn contour(x0: f32, y0: f32, x1: f32, y1: f32, clockwise: bool) -> Vec<(f32, f32)> {
+    let mut points = rect(x0, y0, x1, y1).points().to_vec();
+    if !clockwise {
+        points.reverse();
+    }
+    points
+}
+
+fn areas(polylines: &[Polyline]) -> Vec<f32> {
+    let mut areas = polylines.iter().map(|p| p.area()).collect::<Vec<_>>();
+    areas.sort_by(f32::total_cmp);
+    areas
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-3, "{} != {}", a, b);
+}
+
+#[test]
+fn boolean_operations() {
+    let a = rect(0.0, 0.0, 10.0, 10.0);
+    let b = rect(5.0, 5.0, 15.0, 15.0);
+    let far = rect(20.0, 20.0, 30.0, 30.0);
+
+    assert_eq!(areas(&a.intersection(&b)), [25.0]);
+    assert_eq!(areas(&a.difference(&b)), [75.0]);
+    assert_eq!(areas(&a.union(&b)), [175.0]);
+
+    assert!(a.intersection(&far).is_empty());
+    assert_eq!(areas(&a.difference(&far)), [100.0]);
+    assert_eq!(areas(&a.union(&far)), [100.0, 100.0]);
+
+    // a hole in the middle is kept as a hole
+    let inner = rect(2.0, 2.0, 8.0, 8.0);
+    let ring = a.difference(&inner);
+    assert_eq!(ring.len(), 1);
+    assert_eq!(ring[0].holes().len(), 1);
+    assert_close(ring[0].area(), 64.0);
+}
+
+#[test]
+fn hole_crossing_the_boundary_is_clipped() {
+    let mut square = rect(0.0, 0.0, 10.0, 10.0);
+    assert!(square.push_hole(rect(5.0, 5.0, 15.0, 15.0)));
+    assert_close(square.area(), 75.0);
+    assert!(square.sdf(7.0, 7.0) > 0.0);
+    assert!(square.sdf(2.0, 7.0) < 0.0);
+
+    // the clipped hole overlaps the new one, they're merged
+    assert!(square.push_hole(rect(4.0, 4.0, 6.0, 6.0)));
+    assert_close(square.area(), 75.0 - (2.0 * 2.0 - 1.0));
+
+    assert!(!square.push_hole(rect(20.0, 20.0, 30.0, 30.0)));
+    assert!(!square.push_hole(rect(-1.0, 1.0, 11.0, 2.0)));
+    assert_close(square.area(), 72.0);
+}
+
+#[test]
+fn hole_with_vertices_inside_a_concave_outline() {
+    // a U whose arms hold all the vertices of the hole, with the edges of the
+    // hole crossing the notch between them
+    let mut u = Polyline::new(vec![
+        (0.0, 0.0),
+        (30.0, 0.0),
+        (30.0, 30.0),
+        (20.0, 30.0),
+        (20.0, 10.0),
+        (10.0, 10.0),
+        (10.0, 30.0),
+        (0.0, 30.0),
+    ])
+    .unwrap();
+    assert_close(u.area(), 700.0);
+
+    assert!(u.push_hole(rect(5.0, 15.0, 25.0, 20.0)));
+    assert_close(u.area(), 700.0 - 2.0 * 25.0);
+    assert!(u.sdf(15.0, 17.0) > 0.0);
+}
+
+#[test]
+fn nested_hole_splitting_the_container() {
+    let square = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+    let band = vec![
+        (0.0, 4.0),
+        (5.0, 3.0),
+        (10.0, 4.0),
+        (10.0, 6.0),
+        (5.0, 7.0),
+        (0.0, 6.0),
+    ];
+
+    let containers = nesting::nest(vec![square, band], FillRule::EvenOdd);
+    let areas = areas(&containers);
+    assert_eq!(areas.len(), 2);
+    assert_close(areas[0], 35.0);
+    assert_close(areas[1], 35.0);
+}
+
+#[test]
+fn nest_eight_has_two_holes() {
+    let eight = vec![
+        contour(0.0, 0.0, 10.0, 20.0, true),
+        contour(2.0, 2.0, 8.0, 8.0, false),
+        contour(2.0, 12.0, 8.0, 18.0, false),
+    ];
+
+    for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+        let containers = nesting::nest(eight.clone(), fill_rule);
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].holes().len(), 2);
+        assert_close(containers[0].area(), 200.0 - 2.0 * 36.0);
+    }
+}
+
+#[test]
+fn nest_island_in_a_hole_is_a_container() {
+    // an "@": a ring around a hole holding an island
+    let at = vec![
+        contour(6.0, 6.0, 14.0, 14.0, true),
+        contour(0.0, 0.0, 20.0, 20.0, true),
+        contour(2.0, 2.0, 18.0, 18.0, false),
+    ];
+
+    let containers = nesting::nest(at, FillRule::NonZero);
+    assert_eq!(containers.len(), 2);
+    let (ring, island) = if containers[0].area() > containers[1].area() {
+        (&containers[0], &containers[1])
+    } else {
+        (&containers[1], &containers[0])
+    };
+    assert_eq!(ring.holes().len(), 1);
+    assert_close(ring.area(), 400.0 - 256.0);
+    assert!(island.holes().is_empty());
+    assert_close(island.area(), 64.0);
+    assert!(ring.sdf(10.0, 10.0) > 0.0 && island.sdf(10.0, 10.0) < 0.0);
+}
+
+#[test]
+fn nest_disjoint_outers() {
+    let contours = vec![
+        contour(0.0, 0.0, 10.0, 10.0, true),
+        contour(20.0, 0.0, 25.0, 10.0, true),
+        // too short to be a contour
+        vec![(30.0, 0.0), (31.0, 1.0)],
+    ];
+
+    let containers = nesting::nest(contours, FillRule::NonZero);
+    assert!(containers.iter().all(|c| c.holes().is_empty()));
+    assert_eq!(areas(&containers), [50.0, 100.0]);
+}
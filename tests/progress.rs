@@ -0,0 +1,100 @@
+use std::{ops::ControlFlow, sync::atomic::AtomicBool};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{
+    pack_with_observer,
+    progress::{Observer, Progress, PROGRESS_INTERVAL},
+    Circle, PackShape, Packable, RegularPolygon, Settings, StopCriterion,
+};
+
+/// Stops after placing the given number of shapes.
+struct Count(usize);
+
+impl<C: Packable> Observer<C> for Count {
+    fn placed(&mut self, _shape: &PackShape<C, C>, progress: &Progress) -> ControlFlow<()> {
+        if progress.placed >= self.0 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+fn count(shape: &PackShape<Circle, Circle>) -> usize {
+    1 + shape.children().iter().map(count).sum::<usize>()
+}
+
+#[test]
+fn observer_stops_packing() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let mut root = PackShape::new(RegularPolygon::new(150.0, 100.0, 90.0, 6));
+    let child = Circle::new(0.0, 0.0, 1.0);
+    let cfg = Settings::default();
+
+    let progress = pack_with_observer(
+        &mut root,
+        &child,
+        &cfg,
+        |_, _| f32::INFINITY,
+        &mut Count(10),
+        &mut rng,
+    );
+
+    assert_eq!(progress.placed, 10);
+    assert_eq!(root.children().iter().map(count).sum::<usize>(), 10);
+}
+
+#[test]
+fn cancelled_packing_stops_at_next_progress() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let mut root = PackShape::new(RegularPolygon::new(150.0, 100.0, 90.0, 6));
+    let child = Circle::new(0.0, 0.0, 1.0);
+    let cfg = Settings::default();
+
+    let cancel = AtomicBool::new(true);
+    let progress = pack_with_observer(
+        &mut root,
+        &child,
+        &cfg,
+        |_, _| f32::INFINITY,
+        &mut &cancel,
+        &mut rng,
+    );
+
+    assert_eq!(progress.attempts, PROGRESS_INTERVAL);
+}
+
+#[test]
+fn stop_criterion_places_exact_count() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let mut root = PackShape::new(RegularPolygon::new(150.0, 100.0, 90.0, 6));
+    let cfg = Settings {
+        stop: Some("any(placed:25,attempts:100000)".parse().unwrap()),
+        ..Settings::default()
+    };
+
+    circle_packing::pack(&mut root, &cfg, &mut rng);
+
+    assert_eq!(root.children().iter().map(count).sum::<usize>(), 25);
+}
+
+#[test]
+fn stop_criterion_round_trips() {
+    let stop = StopCriterion::Any(vec![
+        StopCriterion::All(vec![
+            StopCriterion::Coverage(0.5),
+            StopCriterion::AcceptanceRate(0.01),
+        ]),
+        StopCriterion::Time(2.5),
+        StopCriterion::Placed(500),
+    ]);
+
+    assert_eq!(stop.to_string().parse::<StopCriterion>(), Ok(stop));
+    assert!("any(placed:1".parse::<StopCriterion>().is_err());
+    assert!("sometimes:3".parse::<StopCriterion>().is_err());
+    assert!("any()".parse::<StopCriterion>().is_err());
+    assert!("all( )".parse::<StopCriterion>().is_err());
+    assert!("any(placed:1,all())".parse::<StopCriterion>().is_err());
+}
@@ -0,0 +1,32 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{pack, raster, Bbox, Circle, PackShape, Settings, StopCriterion};
+
+#[test]
+fn png_decodes_at_the_requested_size() {
+    let mut rng = ChaCha8Rng::seed_from_u64(4);
+    let cfg = Settings {
+        min_radius: 4.0,
+        padding: 1.0,
+        stop: Some(StopCriterion::Placed(30)),
+        ..Settings::default()
+    };
+
+    let mut bbox = Bbox::new(0.0, 0.0);
+    bbox.expand(200.0, 100.0);
+    let mut root = PackShape::<Bbox, Circle>::new(bbox);
+    pack(&mut root, &cfg, &mut rng);
+    let roots = std::slice::from_ref(&root);
+
+    for (scale, size) in [(1.0, (200, 100)), (0.5, (100, 50)), (1.5, (300, 150))] {
+        let mut png = vec![];
+        raster::dump_png(&mut png, roots, &cfg, scale, 2).unwrap();
+
+        let image = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+            .unwrap()
+            .to_rgb8();
+        assert_eq!(image.dimensions(), size);
+        assert_eq!(image, raster::render(roots, &cfg, scale, 2));
+    }
+}
@@ -0,0 +1,155 @@
+use std::{fs, path::Path, process::Command};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{
+    dump_svg, pack,
+    tree::{self, Format, Packing},
+    CandidateSampler, PackShape, Placement, RegularPolygon, Settings,
+};
+
+fn settings() -> Settings {
+    Settings {
+        min_radius: 5.0,
+        padding: 2.0,
+        inside: true,
+        palette: vec![
+            "#172a89".to_string(),
+            "#f7f7f3".to_string(),
+            "#ee5d65".to_string(),
+        ],
+        target_area: 0.7,
+        max_stall_iterations: 500,
+        stop: None,
+        placement: Placement::First,
+        sampler: CandidateSampler::Uniform,
+    }
+}
+
+fn packed_svg(seed: u64) -> Vec<u8> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    let mut root = PackShape::new(RegularPolygon::new(150.0, 100.0, 90.0, 6));
+    pack(&mut root, &settings(), &mut rng);
+
+    let mut svg = vec![];
+    dump_svg(&mut svg, &[root], &settings()).unwrap();
+    svg
+}
+
+/// FNV-1a hash of the given bytes, to pin outputs without storing them.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |h, &b| {
+        (h ^ u64::from(b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[test]
+fn same_seed_same_svg() {
+    assert_eq!(packed_svg(42), packed_svg(42));
+    assert_ne!(packed_svg(42), packed_svg(43));
+}
+
+/// Hash of `packed_svg(42)`, changes to the packing that alter the output for
+/// a given seed must update it on purpose.
+const PINNED: u64 = 0xfca452ae9919af5b;
+
+#[test]
+fn pinned_svg() {
+    assert_eq!(fnv1a(&packed_svg(42)), PINNED);
+}
+
+#[test]
+fn tree_round_trip() {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let mut root = PackShape::new(RegularPolygon::new(150.0, 100.0, 90.0, 6));
+    pack(&mut root, &settings(), &mut rng);
+    let packing = Packing {
+        settings: settings(),
+        roots: vec![root],
+    };
+
+    for format in [Format::Json, Format::Binary] {
+        let mut data = vec![];
+        tree::write(&mut data, &packing, format).unwrap();
+        let loaded: Packing<RegularPolygon> = tree::read(&data, format).unwrap();
+
+        let mut svg = vec![];
+        dump_svg(&mut svg, &loaded.roots, &loaded.settings).unwrap();
+        assert_eq!(fnv1a(&svg), PINNED);
+    }
+}
+
+fn run_cli(args: &[&str]) -> String {
+    let out = Command::new(env!("CARGO_BIN_EXE_circle-packing"))
+        .args(args)
+        .output()
+        .unwrap();
+
+    assert!(out.status.success());
+    String::from_utf8(out.stdout).unwrap()
+}
+
+/// The `<circle>` elements of the SVG at the given path.
+fn circles(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter(|l| l.starts_with("<circle"))
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn cli_seed_is_reproducible() {
+    let dir = std::env::temp_dir().join(format!("circle-packing-seed-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let run = |name: &str| {
+        let output = dir.join(name);
+        let out = Command::new(env!("CARGO_BIN_EXE_circle-packing"))
+            .args(["--seed", "1234", "--width", "400", "--height", "300"])
+            .arg("--output")
+            .arg(&output)
+            .output()
+            .unwrap();
+
+        assert!(out.status.success());
+        assert!(String::from_utf8_lossy(&out.stdout).contains("using seed 1234"));
+        fs::read(output).unwrap()
+    };
+
+    let (a, b) = (run("a.svg"), run("b.svg"));
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(a, b);
+}
+
+#[test]
+fn cli_regenerate_from_metadata() {
+    let dir = std::env::temp_dir().join(format!("circle-packing-regen-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let (a, b) = (dir.join("a.svg"), dir.join("b.svg"));
+
+    run_cli(&[
+        "--width",
+        "400",
+        "--height",
+        "300",
+        "-o",
+        a.to_str().unwrap(),
+    ]);
+
+    let metadata = circle_packing::metadata::load(&a).unwrap();
+    assert!(metadata.seed.is_some());
+    assert_eq!(metadata.container.as_deref(), Some("400x300 rectangle"));
+    assert!(!metadata.args.iter().any(|a| a == "-o"));
+
+    run_cli(&["regenerate", a.to_str().unwrap(), "-o", b.to_str().unwrap()]);
+    let (ca, cb) = (circles(&a), circles(&b));
+
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(!ca.is_empty());
+    assert_eq!(ca, cb);
+}
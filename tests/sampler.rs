@@ -0,0 +1,58 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{CandidateSampler, RegularPolygon, Shape};
+
+const SAMPLERS: &[&str] = &["uniform", "halton", "sobol", "jitter:4", "poisson:4"];
+
+#[test]
+fn candidates_are_inside_the_shape() {
+    let shape = RegularPolygon::new(150.0, 100.0, 90.0, 6);
+
+    for s in SAMPLERS {
+        let sampler: CandidateSampler = s.parse().unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let mut candidates = sampler.candidates(&shape.bbox(), 2.0, &mut rng);
+
+        for _ in 0..5000 {
+            let (x, y) = candidates.next_in(&shape, &mut rng);
+            assert!(shape.sdf(x, y) < 0.0, "{} yields {}, {}", s, x, y);
+        }
+    }
+}
+
+#[test]
+fn sampler_round_trips() {
+    for s in SAMPLERS {
+        let sampler: CandidateSampler = s.parse().unwrap();
+        assert_eq!(sampler.to_string(), *s);
+    }
+    assert!("jitter:-1".parse::<CandidateSampler>().is_err());
+    assert!("grid".parse::<CandidateSampler>().is_err());
+}
+
+#[test]
+fn sampler_rejects_extra_segments() {
+    for s in &["jitter:5:7", "poisson:4:", "uniform:3", "sobol:"] {
+        assert!(s.parse::<CandidateSampler>().is_err(), "{} parses", s);
+    }
+    for s in &["jitter:inf", "poisson:NaN", "jitter:0"] {
+        assert!(s.parse::<CandidateSampler>().is_err(), "{} parses", s);
+    }
+}
+
+#[test]
+fn zero_min_radius_terminates() {
+    let shape = RegularPolygon::new(150.0, 100.0, 90.0, 6);
+
+    for s in &["jitter", "poisson"] {
+        let sampler: CandidateSampler = s.parse().unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        let mut candidates = sampler.candidates(&shape.bbox(), 0.0, &mut rng);
+
+        for _ in 0..100 {
+            let (x, y) = candidates.next_in(&shape, &mut rng);
+            assert!(shape.sdf(x, y) < 0.0, "{} yields {}, {}", s, x, y);
+        }
+    }
+}
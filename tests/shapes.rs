@@ -0,0 +1,74 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{Capsule, Polyline, RegularPolygon, Shape, Star};
+
+#[test]
+fn star_sdf_matches_its_polygon() {
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+
+    for (points, inner, rotation) in [(5, 4.0, 0.0), (2, 3.0, 0.3), (7, 9.0, -2.0), (12, 1.0, 4.0)]
+    {
+        let mut star = Star::new(3.0, -2.0, 10.0, inner, points);
+        star.rotation = rotation;
+        let polygon = Polyline::new(star.vertices()).unwrap();
+
+        for _ in 0..1000 {
+            let (x, y) = (rng.gen_range(-15.0..20.0), rng.gen_range(-20.0..15.0));
+            let (d, expected) = (star.sdf(x, y), polygon.sdf(x, y));
+            assert!(
+                (d - expected).abs() < 1e-3,
+                "{} points at {:?}: {} != {}",
+                points,
+                (x, y),
+                d,
+                expected
+            );
+        }
+
+        for (x, y) in star.vertices() {
+            assert!(star.sdf(x, y).abs() < 1e-3);
+        }
+        assert!(star.sdf(3.0, -2.0) < 0.0);
+    }
+}
+
+#[test]
+fn regular_polygon_sdf_is_zero_on_its_vertices() {
+    for (sides, rotation) in [(3, 0.0), (4, 0.7), (6, -1.2), (11, 3.0)] {
+        let mut polygon = RegularPolygon::new(-4.0, 6.0, 10.0, sides);
+        polygon.rotation = rotation;
+
+        let vertices = polygon.vertices().collect::<Vec<_>>();
+        assert_eq!(vertices.len(), sides as usize);
+        for (i, &(x, y)) in vertices.iter().enumerate() {
+            assert!(
+                polygon.sdf(x, y).abs() < 1e-3,
+                "{} sides: vertex {}",
+                sides,
+                i
+            );
+
+            // the middle of each edge is an apothem away from the center
+            let (nx, ny) = vertices[(i + 1) % vertices.len()];
+            assert!(polygon.sdf((x + nx) / 2.0, (y + ny) / 2.0).abs() < 1e-3);
+        }
+        assert!((polygon.sdf(-4.0, 6.0) + polygon.apothem()).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn capsule_without_radius_gives_its_midpoint() {
+    let mut rng = ChaCha8Rng::seed_from_u64(6);
+
+    for radius in [0.0, -1.0] {
+        let capsule = Capsule::new((0.0, 0.0), (10.0, 4.0), radius);
+        assert_eq!(capsule.random_point(&mut rng), (5.0, 2.0));
+    }
+
+    let capsule = Capsule::new((0.0, 0.0), (10.0, 4.0), 2.0);
+    for _ in 0..1000 {
+        let (x, y) = capsule.random_point(&mut rng);
+        assert!(capsule.sdf(x, y) <= 1e-3);
+    }
+}
@@ -0,0 +1,152 @@
+use std::{fs, process::Command};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use circle_packing::{
+    dump_svg, pack, pdf, plot, raster,
+    svg::{self, SvgError},
+    Bbox, Circle, PackShape, Settings, Shape, StopCriterion,
+};
+
+fn element(name: &str, attributes: &str) -> Result<Vec<circle_packing::Polyline>, SvgError> {
+    svg::parse(
+        &format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><{} {}/></svg>"#,
+            name, attributes
+        ),
+        1e-3,
+    )
+}
+
+fn rect(attributes: &str) -> Result<Vec<circle_packing::Polyline>, SvgError> {
+    element("rect", attributes)
+}
+
+/// The attribute reported as invalid when parsing the given element.
+fn invalid_in(name: &str, attributes: &str) -> &'static str {
+    match element(name, attributes) {
+        Err(SvgError::InvalidAttribute { element, attribute }) => {
+            assert_eq!(element, name);
+            attribute
+        }
+        other => panic!("<{} {}> gave {:?}", name, attributes, other),
+    }
+}
+
+fn invalid_attribute(attributes: &str) -> &'static str {
+    invalid_in("rect", attributes)
+}
+
+#[test]
+fn rect_rejects_invalid_sizes() {
+    assert_eq!(invalid_attribute(r#"width="-10" height="5""#), "width");
+    assert_eq!(invalid_attribute(r#"width="10" height="NaN""#), "height");
+    assert_eq!(invalid_attribute(r#"width="inf" height="5""#), "width");
+    assert_eq!(invalid_attribute(r#"width="10" height="5" rx="-1""#), "rx");
+    assert_eq!(
+        invalid_attribute(r#"width="10" height="5" ry="oops""#),
+        "ry"
+    );
+    assert_eq!(invalid_attribute(r#"width="10" height="5" rx="1em""#), "rx");
+}
+
+#[test]
+fn circle_and_ellipse_reject_invalid_radii() {
+    assert_eq!(invalid_in("circle", r#"r="-5""#), "r");
+    assert_eq!(invalid_in("circle", r#"cx="1" r="NaN""#), "r");
+    assert_eq!(invalid_in("circle", r#"r="inf""#), "r");
+    assert_eq!(invalid_in("ellipse", r#"rx="-3" ry="2""#), "rx");
+    assert_eq!(invalid_in("ellipse", r#"rx="3" ry="-inf""#), "ry");
+    assert_eq!(invalid_in("ellipse", r#"rx="3" ry="nan""#), "ry");
+
+    let circle = &element("circle", r#"cx="5" cy="5" r="2""#).unwrap()[0];
+    assert!((circle.area() - std::f32::consts::PI * 4.0).abs() < 0.01);
+}
+
+#[test]
+fn rect_radii() {
+    assert!(rect(r#"width="0" height="5""#).unwrap().is_empty());
+
+    let plain = &rect(r#"x="1" y="2" width="10" height="5""#).unwrap()[0];
+    assert!((plain.area() - 50.0).abs() < 1e-3);
+
+    // a missing radius defaults to the other one, and both are at most half
+    // of the side
+    let round = &rect(r#"width="10" height="4" rx="20""#).unwrap()[0];
+    let expected = 10.0 * 4.0 - (4.0 - std::f32::consts::PI) * 5.0 * 2.0;
+    assert!((round.area() - expected).abs() < 0.05, "{}", round.area());
+}
+
+#[test]
+fn packs_into_dumped_svg() {
+    let mut rng = ChaCha8Rng::seed_from_u64(2);
+    let cfg = Settings {
+        min_radius: 4.0,
+        padding: 1.0,
+        stop: Some(StopCriterion::Placed(30)),
+        ..Settings::default()
+    };
+
+    let mut bbox = Bbox::new(0.0, 0.0);
+    bbox.expand(200.0, 100.0);
+    let mut root = PackShape::<Bbox, Circle>::new(bbox);
+    pack(&mut root, &cfg, &mut rng);
+
+    // the output has the SVG 1.1 doctype
+    let mut out = vec![];
+    dump_svg(&mut out, std::slice::from_ref(&root), &cfg).unwrap();
+    let data = String::from_utf8(out).unwrap();
+    assert!(data.contains("<!DOCTYPE svg"));
+
+    let containers = svg::parse(&data, 0.1).unwrap();
+    assert!(!containers.is_empty());
+
+    let mut root = PackShape::<_, Circle>::new(containers[0].clone());
+    pack(&mut root, &cfg, &mut rng);
+    assert!(!root.children().is_empty());
+}
+
+#[test]
+fn no_roots_is_an_error() {
+    let roots: &[PackShape<Bbox, Circle>] = &[];
+    let cfg = Settings::default();
+
+    assert!(dump_svg(&mut vec![], roots, &cfg).is_err());
+    assert!(pdf::dump_pdf(&mut vec![], roots, &cfg, &pdf::Page::A4).is_err());
+    let plot = plot::PlotSettings::default();
+    assert!(plot::dump_gcode(&mut vec![], roots, &cfg, &plot).is_err());
+    assert!(plot::dump_hpgl(&mut vec![], roots, &cfg, &plot).is_err());
+    assert_eq!(raster::render(roots, &cfg, 1.0, 1).dimensions(), (1, 1));
+}
+
+#[test]
+fn cli_exits_without_shapes() {
+    let dir = std::env::temp_dir().join(format!("circle-packing-empty-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let svg = dir.join("empty.svg");
+    fs::write(
+        &svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg"><text>hi</text></svg>"#,
+    )
+    .unwrap();
+    let scene = dir.join("empty.toml");
+    fs::write(&scene, "containers = []\n").unwrap();
+
+    let run = |args: &[&std::ffi::OsStr]| {
+        Command::new(env!("CARGO_BIN_EXE_circle-packing"))
+            .args(args)
+            .arg("-o")
+            .arg(dir.join("out.svg"))
+            .output()
+            .unwrap()
+    };
+    let from_svg = run(&["-c".as_ref(), svg.as_os_str()]);
+    let from_scene = run(&["--scene".as_ref(), scene.as_os_str()]);
+    fs::remove_dir_all(&dir).unwrap();
+
+    for out in [from_svg, from_scene] {
+        assert!(!out.status.success());
+        assert!(String::from_utf8_lossy(&out.stderr).contains("no shapes found in"));
+    }
+}
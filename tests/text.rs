@@ -0,0 +1,154 @@
+use circle_packing::{
+    text::{self, TextStyle},
+    Shape,
+};
+
+/// Big-endian 16-bit words, 32-bit fields are written as two words.
+fn words(values: &[i32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|&v| (v as u16).to_be_bytes())
+        .collect()
+}
+
+/// A simple glyph made of polygons with on-curve points only, in font units.
+fn glyph(contours: &[&[(i32, i32)]]) -> Vec<u8> {
+    let points = contours.iter().flat_map(|c| c.iter()).collect::<Vec<_>>();
+    let xs = points.iter().map(|p| p.0);
+    let ys = points.iter().map(|p| p.1);
+
+    let mut data = words(&[contours.len() as i32]);
+    data.extend(words(&[
+        xs.clone().min().unwrap(),
+        ys.clone().min().unwrap(),
+        xs.max().unwrap(),
+        ys.max().unwrap(),
+    ]));
+
+    let mut end = -1;
+    for c in contours {
+        end += c.len() as i32;
+        data.extend(words(&[end]));
+    }
+
+    // no instructions, then one on-curve flag per point and the deltas
+    data.extend(words(&[0]));
+    data.extend(points.iter().map(|_| 1u8));
+    let mut previous = (0, 0);
+    let mut dys = vec![];
+    for &&(x, y) in &points {
+        data.extend(words(&[x - previous.0]));
+        dys.push(y - previous.1);
+        previous = (x, y);
+    }
+    data.extend(words(&dys));
+
+    data
+}
+
+/// A font with 1000 units per em whose glyphs are an "o" made of two squares,
+/// and an "A" and a "V" made of triangles 600 units wide with a kerning of
+/// -100 units between them.
+fn font() -> Vec<u8> {
+    let glyphs = [
+        vec![],
+        glyph(&[
+            &[(100, 0), (100, 400), (500, 400), (500, 0)],
+            &[(200, 100), (400, 100), (400, 300), (200, 300)],
+        ]),
+        glyph(&[&[(0, 0), (300, 700), (600, 0)]]),
+        glyph(&[&[(0, 700), (600, 700), (300, 0)]]),
+    ];
+
+    let mut glyf = vec![];
+    let mut loca = vec![];
+    for g in &glyphs {
+        loca.extend((glyf.len() as u32).to_be_bytes());
+        glyf.extend(g);
+    }
+    loca.extend((glyf.len() as u32).to_be_bytes());
+
+    let mut head = words(&[1, 0, 0, 0, 0, 0, 0x5F0F, 0x3CF5, 0, 1000]);
+    head.extend(words(&[0; 8]));
+    head.extend(words(&[0, -200, 600, 800, 0, 0, 2, 1, 0]));
+
+    let mut hhea = words(&[1, 0, 800, -200, 0]);
+    hhea.extend(words(&[0; 12]));
+    hhea.extend(words(&[glyphs.len() as i32]));
+
+    let hmtx = words(&[500, 0, 600, 100, 600, 0, 600, 0]);
+    let maxp = words(&[0, 0x5000, glyphs.len() as i32]);
+
+    // a format 6 subtable mapping "A" to "o"
+    let mut cmap_glyphs = vec![0; (b'o' - b'A' + 1) as usize];
+    cmap_glyphs[(b'o' - b'A') as usize] = 1;
+    cmap_glyphs[0] = 2;
+    cmap_glyphs[(b'V' - b'A') as usize] = 3;
+    let mut cmap = words(&[0, 1, 0, 3, 0, 12]);
+    cmap.extend(words(&[6, 10 + 2 * cmap_glyphs.len() as i32, 0]));
+    cmap.extend(words(&[b'A' as i32, cmap_glyphs.len() as i32]));
+    cmap.extend(words(&cmap_glyphs));
+
+    let kern = words(&[0, 1, 0, 20, 0x0001, 1, 6, 0, 0, 2, 3, -100]);
+
+    let mut tables = vec![
+        (b"cmap", cmap),
+        (b"glyf", glyf),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"kern", kern),
+        (b"loca", loca),
+        (b"maxp", maxp),
+    ];
+    tables.sort();
+
+    let mut data = words(&[1, 0, tables.len() as i32, 0, 0, 0]);
+    let mut offset = data.len() + 16 * tables.len();
+    for (tag, table) in &tables {
+        data.extend(*tag);
+        data.extend([0; 4]);
+        data.extend((offset as u32).to_be_bytes());
+        data.extend((table.len() as u32).to_be_bytes());
+        offset += table.len();
+    }
+    for (_, table) in tables {
+        data.extend(table);
+    }
+
+    data
+}
+
+fn style() -> TextStyle {
+    TextStyle {
+        size: 100.0,
+        letter_spacing: 0.0,
+        tolerance: 0.1,
+    }
+}
+
+#[test]
+fn counters_become_holes() {
+    let containers = text::outlines(&font(), "o", &style()).unwrap();
+
+    assert_eq!(containers.len(), 1);
+    assert_eq!(containers[0].holes().len(), 1);
+    assert!((containers[0].area() - (40.0 * 40.0 - 20.0 * 20.0)).abs() < 1e-2);
+
+    // the top of the line is at y = 0 and the baseline at the ascender
+    let bbox = containers[0].bbox();
+    assert!((bbox.y0() - 40.0).abs() < 1e-3 && (bbox.y1() - 80.0).abs() < 1e-3);
+}
+
+#[test]
+fn kerned_pair_is_narrower_than_its_advances() {
+    let right = |text| {
+        let containers = text::outlines(&font(), text, &style()).unwrap();
+        assert_eq!(containers.len(), 2);
+        containers[1].bbox().x1()
+    };
+
+    // both glyphs are 60 wide, the pair is only kerned in this order
+    assert!((right("VA") - 120.0).abs() < 1e-3);
+    assert!((right("AV") - 110.0).abs() < 1e-3);
+}